@@ -0,0 +1,528 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use reqwest::header::{self, HeaderName, HeaderValue};
+use reqwest::{Request, Url};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+use crate::auth::{Auth, AuthScheme};
+use crate::cli::RequestArgs;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    /// `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` (and `AWS_SESSION_TOKEN`).
+    pub fn from_env() -> Option<Self> {
+        Some(Credentials {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    /// Credentials of `profile` in an `~/.aws/credentials` file.
+    pub fn from_profile(text: &str, profile: &str) -> Option<Self> {
+        let section = parse_ini(text).remove(profile)?;
+        Some(Credentials {
+            access_key_id: section.get("aws_access_key_id")?.clone(),
+            secret_access_key: section.get("aws_secret_access_key")?.clone(),
+            session_token: section.get("aws_session_token").cloned(),
+        })
+    }
+
+    /// Keys given with `--auth`, else the environment, else the shared
+    /// credentials file (`--aws-profile`, `$AWS_PROFILE` or `default`).
+    pub fn resolve(access_key: Option<&str>, secret_key: Option<&str>, profile: Option<&str>) -> Result<Self> {
+        if let (Some(access_key_id), Some(secret_access_key)) = (access_key, secret_key) {
+            return Ok(Credentials {
+                access_key_id: access_key_id.to_string(),
+                secret_access_key: secret_access_key.to_string(),
+                session_token: None,
+            });
+        }
+        if profile.is_none()
+            && let Some(credentials) = Self::from_env()
+        {
+            return Ok(credentials);
+        }
+
+        let profile = profile_name(profile);
+        let path = aws_file("AWS_SHARED_CREDENTIALS_FILE", "credentials")
+            .ok_or_else(|| anyhow!("Could not locate the AWS credentials file"))?;
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        Self::from_profile(&text, &profile).ok_or_else(|| {
+            anyhow!(
+                "No AWS credentials found: set AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY, \
+                 add profile '{}' to {}, or pass --auth ACCESS_KEY:SECRET_KEY",
+                profile,
+                path.display()
+            )
+        })
+    }
+}
+
+fn profile_name(profile: Option<&str>) -> String {
+    profile
+        .map(str::to_string)
+        .or_else(|| std::env::var("AWS_PROFILE").ok())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// `$<env>`, or `~/.aws/<name>`.
+fn aws_file(env: &str, name: &str) -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(env) {
+        return Some(PathBuf::from(path));
+    }
+    dirs::home_dir().map(|home| home.join(".aws").join(name))
+}
+
+/// Sections of an AWS INI file; `[profile name]` headers (used in
+/// `~/.aws/config`) are stored under `name`.
+fn parse_ini(text: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            let name = name.trim();
+            let name = name.strip_prefix("profile ").unwrap_or(name).trim().to_string();
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+        } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    sections
+}
+
+fn is_region(label: &str) -> bool {
+    let parts: Vec<&str> = label.split('-').collect();
+    parts.len() >= 3
+        && parts[0].len() == 2
+        && parts[0].bytes().all(|b| b.is_ascii_lowercase())
+        && parts.last().is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Region and service from an `*.amazonaws.com` host, e.g.
+/// `sqs.eu-west-1.amazonaws.com` or `search-logs-x.us-east-1.es.amazonaws.com`.
+pub fn infer_from_host(host: &str) -> (Option<String>, Option<String>) {
+    let Some(prefix) = host.strip_suffix(".amazonaws.com") else {
+        return (None, None);
+    };
+    let labels: Vec<&str> = prefix.split('.').collect();
+    let Some(index) = labels.iter().position(|label| is_region(label)) else {
+        return (None, None);
+    };
+    let region = Some(labels[index].to_string());
+    let service = match labels.get(index + 1) {
+        Some(service) => Some(service.to_string()),
+        None if index > 0 => {
+            let service = labels[index - 1];
+            // `bucket.s3-accelerate`, `s3-website` etc. are all signed as s3
+            Some(if service.starts_with("s3") { "s3" } else { service }.to_string())
+        }
+        None => None,
+    };
+    (region, service)
+}
+
+/// Region and service for a request: `--aws-region` / `--aws-service`, then
+/// the host name, then `$AWS_REGION`, `$AWS_DEFAULT_REGION` and the profile's
+/// region in `~/.aws/config`.
+pub fn region_and_service(args: &RequestArgs, url: &Url) -> Result<(String, String)> {
+    let (host_region, host_service) = infer_from_host(url.host_str().unwrap_or_default());
+
+    let region = args
+        .aws_region
+        .clone()
+        .or(host_region)
+        .or_else(|| std::env::var("AWS_REGION").ok())
+        .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok())
+        .or_else(|| {
+            let path = aws_file("AWS_CONFIG_FILE", "config")?;
+            let text = std::fs::read_to_string(path).ok()?;
+            parse_ini(&text)
+                .remove(&profile_name(args.aws_profile.as_deref()))?
+                .remove("region")
+        })
+        .ok_or_else(|| anyhow!("Could not determine the AWS region; pass --aws-region"))?;
+    let service = args
+        .aws_service
+        .clone()
+        .or(host_service)
+        .ok_or_else(|| anyhow!("Could not determine the AWS service; pass --aws-service (e.g. s3, execute-api, es)"))?;
+    Ok((region, service))
+}
+
+/// URI-encodes `s` as SigV4 requires: everything but unreserved characters.
+fn uri_encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = s.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn canonical_uri(url: &Url, service: &str) -> String {
+    let path = url.path();
+    let path = if path.is_empty() { "/" } else { path };
+    // Segments are re-encoded one by one so an escaped `/` stays escaped
+    let encoded: Vec<String> = path
+        .split('/')
+        .map(|segment| uri_encode(&percent_decode(segment), false))
+        .collect();
+    let encoded = encoded.join("/");
+    // Every service except S3 expects the path to be encoded twice
+    if service == "s3" {
+        encoded
+    } else {
+        uri_encode(&encoded, true)
+    }
+}
+
+fn canonical_query(url: &Url) -> String {
+    // reqwest's `.query()` writes a space as `+` and a literal `+` as `%2B`,
+    // so `+` is decoded as a space and signed as `%20`
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key, false), uri_encode(&value, false)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn amz_date(time: OffsetDateTime) -> String {
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        time.year(),
+        time.month() as u8,
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// `--auth-type aws-sigv4`; `--auth ACCESS_KEY:SECRET_KEY` is optional.
+pub struct AwsSigV4Scheme;
+
+impl AuthScheme for AwsSigV4Scheme {
+    fn name(&self) -> &'static str {
+        "aws-sigv4"
+    }
+
+    fn credentials(&self, auth: Auth) -> Result<Auth> {
+        match auth {
+            Auth::Basic {
+                username,
+                password: Some(password),
+            } => Ok(Auth::AwsSigV4 {
+                access_key: Some(username),
+                secret_key: Some(password),
+            }),
+            auth @ Auth::AwsSigV4 { .. } => Ok(auth),
+            _ => Err(anyhow!("AWS SigV4 auth needs credentials as ACCESS_KEY:SECRET_KEY")),
+        }
+    }
+
+    fn default_credentials(&self) -> Option<Auth> {
+        Some(Auth::AwsSigV4 {
+            access_key: None,
+            secret_key: None,
+        })
+    }
+}
+
+/// Signs `request` in place: adds `Host`, `X-Amz-Date` (and for S3
+/// `X-Amz-Content-SHA256`, for temporary credentials `X-Amz-Security-Token`)
+/// and the `Authorization` header.
+pub fn sign(
+    request: &mut Request,
+    credentials: &Credentials,
+    region: &str,
+    service: &str,
+    time: OffsetDateTime,
+) -> Result<()> {
+    let url = request.url().clone();
+    let host = url.host_str().ok_or_else(|| anyhow!("URL has no host: {}", url))?;
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let amz_date = amz_date(time.to_offset(time::UtcOffset::UTC));
+    let date = &amz_date[..8];
+
+    // Streamed bodies (multipart uploads) can't be hashed up front
+    let payload_hash = match request.body().map(|body| body.as_bytes()) {
+        None => hex(&Sha256::digest(b"")),
+        Some(Some(bytes)) => hex(&Sha256::digest(bytes)),
+        Some(None) if service == "s3" => "UNSIGNED-PAYLOAD".to_string(),
+        Some(None) => return Err(anyhow!("AWS SigV4 can't sign a streamed body")),
+    };
+
+    let headers = request.headers_mut();
+    headers.insert(header::HOST, HeaderValue::from_str(&host)?);
+    headers.insert("x-amz-date", HeaderValue::from_str(&amz_date)?);
+    if service == "s3" {
+        headers.insert("x-amz-content-sha256", HeaderValue::from_str(&payload_hash)?);
+    }
+    if let Some(token) = &credentials.session_token {
+        headers.insert("x-amz-security-token", HeaderValue::from_str(token)?);
+    }
+
+    // Sign the headers that proxies leave alone
+    let mut signed: Vec<(String, String)> = Vec::new();
+    for name in headers.keys() {
+        let name = name.as_str();
+        if name == "host" || name == "content-type" || name == "content-md5" || name.starts_with("x-amz-") {
+            let values: Vec<String> = headers
+                .get_all(name)
+                .iter()
+                .map(|value| {
+                    String::from_utf8_lossy(value.as_bytes())
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect();
+            signed.push((name.to_string(), values.join(",")));
+        }
+    }
+    signed.sort();
+    let canonical_headers: String = signed
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers = signed
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method().as_str(),
+        canonical_uri(&url, service),
+        canonical_query(&url),
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = hmac(format!("AWS4{}", credentials.secret_access_key).as_bytes(), date);
+    let key = hmac(&key, region);
+    let key = hmac(&key, service);
+    let key = hmac(&key, "aws4_request");
+    let signature = hex(&hmac(&key, &string_to_sign));
+
+    let authorization = format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        ALGORITHM, credentials.access_key_id, scope, signed_headers, signature
+    );
+    request
+        .headers_mut()
+        .insert(HeaderName::from_static("authorization"), HeaderValue::from_str(&authorization)?);
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Method;
+
+    // Credentials and time used by the AWS SigV4 test suite
+    fn test_credentials() -> Credentials {
+        Credentials {
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            session_token: None,
+        }
+    }
+
+    fn test_time() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1440938160).unwrap() // 2015-08-30T12:36:00Z
+    }
+
+    fn signed(method: Method, url: &str, service: &str) -> Request {
+        let mut request = Request::new(method, Url::parse(url).unwrap());
+        sign(&mut request, &test_credentials(), "us-east-1", service, test_time()).unwrap();
+        request
+    }
+
+    fn authorization(request: &Request) -> &str {
+        request.headers()["authorization"].to_str().unwrap()
+    }
+
+    #[test]
+    fn sigv4_get_vanilla() {
+        let request = signed(Method::GET, "https://example.amazonaws.com/", "service");
+        assert_eq!(
+            authorization(&request),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert_eq!(request.headers()["x-amz-date"], "20150830T123600Z");
+    }
+
+    #[test]
+    fn sigv4_query_is_sorted() {
+        let request = signed(
+            Method::GET,
+            "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+            "service",
+        );
+        assert!(authorization(&request)
+            .ends_with("Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"));
+    }
+
+    #[test]
+    fn sigv4_s3_hashes_payload() {
+        let mut request = Request::new(Method::PUT, Url::parse("http://localhost:9000/bucket/key").unwrap());
+        *request.body_mut() = Some("hello".into());
+        sign(&mut request, &test_credentials(), "us-east-1", "s3", test_time()).unwrap();
+
+        assert_eq!(
+            request.headers()["x-amz-content-sha256"],
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(request.headers()["host"], "localhost:9000");
+        assert!(authorization(&request).contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,"));
+    }
+
+    #[test]
+    fn sigv4_session_token_is_signed() {
+        let mut request = Request::new(Method::GET, Url::parse("https://example.amazonaws.com/").unwrap());
+        let credentials = Credentials {
+            session_token: Some("token".into()),
+            ..test_credentials()
+        };
+        sign(&mut request, &credentials, "us-east-1", "service", test_time()).unwrap();
+        assert_eq!(request.headers()["x-amz-security-token"], "token");
+        assert!(authorization(&request).contains("SignedHeaders=host;x-amz-date;x-amz-security-token,"));
+    }
+
+    #[test]
+    fn canonical_uri_encoding() {
+        let url = Url::parse("https://example.com/documents and settings/a%2Fb").unwrap();
+        assert_eq!(canonical_uri(&url, "s3"), "/documents%20and%20settings/a%2Fb");
+        assert_eq!(canonical_uri(&url, "es"), "/documents%2520and%2520settings/a%252Fb");
+        assert_eq!(canonical_uri(&Url::parse("https://example.com").unwrap(), "s3"), "/");
+    }
+
+    #[test]
+    fn canonical_query_encoding() {
+        let url = Url::parse("https://example.com/?q=a+b&tag=c%2Bd&empty&x=1%202").unwrap();
+        assert_eq!(canonical_query(&url), "empty=&q=a%20b&tag=c%2Bd&x=1%202");
+        assert_eq!(canonical_query(&Url::parse("https://example.com/").unwrap()), "");
+
+        // As query items are sent: a space goes on the wire as `+`
+        let request = reqwest::Client::new()
+            .get("https://example.com/")
+            .query(&[("q", "a b"), ("tag", "c+d")])
+            .build()
+            .unwrap();
+        assert_eq!(request.url().query(), Some("q=a+b&tag=c%2Bd"));
+        assert_eq!(canonical_query(request.url()), "q=a%20b&tag=c%2Bd");
+    }
+
+    #[test]
+    fn infer_region_and_service() {
+        assert_eq!(
+            infer_from_host("sqs.eu-west-1.amazonaws.com"),
+            (Some("eu-west-1".into()), Some("sqs".into()))
+        );
+        assert_eq!(
+            infer_from_host("abc123.execute-api.us-east-2.amazonaws.com"),
+            (Some("us-east-2".into()), Some("execute-api".into()))
+        );
+        assert_eq!(
+            infer_from_host("search-logs-x.ap-south-1.es.amazonaws.com"),
+            (Some("ap-south-1".into()), Some("es".into()))
+        );
+        assert_eq!(
+            infer_from_host("bucket.s3.us-gov-west-1.amazonaws.com"),
+            (Some("us-gov-west-1".into()), Some("s3".into()))
+        );
+        assert_eq!(infer_from_host("localhost"), (None, None));
+    }
+
+    #[test]
+    fn credentials_from_profile() {
+        let text = "[default]\naws_access_key_id = AKID1\naws_secret_access_key = secret1\n\n\
+                    [profile minio]\naws_access_key_id=minio\naws_secret_access_key=minio123\naws_session_token=tok\n";
+        assert_eq!(
+            Credentials::from_profile(text, "default").unwrap(),
+            Credentials {
+                access_key_id: "AKID1".into(),
+                secret_access_key: "secret1".into(),
+                session_token: None
+            }
+        );
+        assert_eq!(Credentials::from_profile(text, "minio").unwrap().session_token.as_deref(), Some("tok"));
+        assert!(Credentials::from_profile(text, "missing").is_none());
+    }
+}
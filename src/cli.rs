@@ -1,9 +1,16 @@
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::parser::ValueSource;
+use colored::Colorize;
+use clap::{Args, CommandFactory, FromArgMatches, Parser};
 use reqwest::Url;
 use std::str::FromStr;
 
-use crate::auth::Auth;
+use crate::auth::{resolve_auth, Auth, AuthType};
+use crate::config::Config;
+use crate::hmac_auth::Encoding;
+use crate::nested::{parse_path, DuplicateKeys};
+use crate::oauth::Grant;
+use crate::tls::{TlsVersion, Verify};
 
 /// A modern, user-friendly HTTP client written in Rust
 ///
@@ -22,6 +29,10 @@ use crate::auth::Auth;
 ///
 ///   # Download file
 ///   httprs get https://example.com/file.zip -d
+///
+///   # Any other method
+///   httprs request PURGE https://cdn.example.com/asset.js
+///   httprs PROPFIND https://dav.example.com/files/ Depth:1
 #[derive(Parser, Debug)]
 #[clap(version = "1.0", author = "Ethan Bai")]
 pub struct Cli {
@@ -51,6 +62,70 @@ pub enum Command {
 
     /// Make an OPTIONS request
     Options(RequestArgs),
+
+    /// Make a request with any method (PURGE, PROPFIND, MKCOL, REPORT, ...)
+    Request(CustomRequest),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CustomRequest {
+    /// HTTP method, e.g. PURGE, PROPFIND, MKCOL
+    #[arg(value_name = "METHOD", value_parser = parse_method)]
+    pub method: reqwest::Method,
+
+    #[command(flatten)]
+    pub args: RequestArgs,
+}
+
+impl Cli {
+    /// Parses the process arguments, expanding method and URL shorthands, and
+    /// fills in options from the config file.
+    pub fn parse_args() -> Result<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        // `https` binary alias (symlink or copy): default to https
+        let invoked_as_https = args
+            .first()
+            .and_then(|arg0| std::path::Path::new(arg0).file_stem())
+            .is_some_and(|name| name == "https");
+
+        let matches = Cli::command().get_matches_from(expand_method_shorthand(args));
+        let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        let Some((_, sub_matches)) = matches.subcommand() else {
+            unreachable!("a subcommand is required");
+        };
+        let from_cli = |id: &str| sub_matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        let config = Config::load()?;
+        let request = cli.command.args_mut();
+        let raw_url = request.url.clone();
+        let scheme = match &request.default_scheme {
+            Some(scheme) => scheme.as_str(),
+            None if invoked_as_https => "https",
+            None => config.defaults.default_scheme.as_deref().unwrap_or("http"),
+        };
+        request.url = expand_url(&raw_url, scheme)?;
+
+        // A host section may pick another scheme for scheme-less URLs
+        let options = config.options_for(&request.url);
+        if request.default_scheme.is_none()
+            && !invoked_as_https
+            && let Some(host_scheme) = &options.default_scheme
+            && host_scheme != scheme
+        {
+            request.url = expand_url(&raw_url, host_scheme)?;
+        }
+        options.apply(request, from_cli)?;
+        request.auth = resolve_auth(request.auth.take(), request.auth_type)?;
+        if request.auth_type.is_none() && request.auth.as_ref().is_some_and(Auth::looks_like_token) {
+            eprintln!(
+                "{}",
+                "⚠️  Warning: --auth looks like a token but is sent as a Basic username; \
+                 use --auth-type bearer (or auto)"
+                    .yellow()
+            );
+        }
+        Ok(cli)
+    }
 }
 
 impl Command {
@@ -63,6 +138,7 @@ impl Command {
             Command::Delete(_) => reqwest::Method::DELETE,
             Command::Head(_) => reqwest::Method::HEAD,
             Command::Options(_) => reqwest::Method::OPTIONS,
+            Command::Request(request) => request.method.clone(),
         }
     }
 
@@ -75,14 +151,47 @@ impl Command {
             Command::Delete(args) => args,
             Command::Head(args) => args,
             Command::Options(args) => args,
+            Command::Request(request) => &request.args,
+        }
+    }
+
+    pub fn args_mut(&mut self) -> &mut RequestArgs {
+        match self {
+            Command::Get(args) => args,
+            Command::Post(args) => args,
+            Command::Put(args) => args,
+            Command::Patch(args) => args,
+            Command::Delete(args) => args,
+            Command::Head(args) => args,
+            Command::Options(args) => args,
+            Command::Request(request) => &mut request.args,
         }
     }
 }
 
+/// Rewrites `httprs PURGE URL ...` to `httprs request PURGE URL ...` so that
+/// an uppercase method token can be used directly as the subcommand.
+pub fn expand_method_shorthand<I>(args: I) -> Vec<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args: Vec<String> = args.into_iter().collect();
+    let is_method = args.get(1).is_some_and(|token| {
+        token
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b == b'-' || b == b'_')
+            && token.bytes().next().is_some_and(|b| b.is_ascii_uppercase())
+    });
+    if is_method {
+        args.insert(1, "request".to_string());
+    }
+    args
+}
+
 #[derive(Parser, Debug, Clone)]
 pub struct RequestArgs {
-    /// Target URL
-    #[arg(value_name = "URL", value_parser = parse_url)]
+    /// Target URL (`:3000/api` = http://localhost:3000/api, scheme optional)
+    #[arg(value_name = "URL")]
     pub url: String,
 
     /// Request items: headers (Key:Value), query params (key==value), body (key=value)
@@ -93,6 +202,62 @@ pub struct RequestArgs {
     #[arg(short = 'a', long = "auth", value_parser = parse_auth)]
     pub auth: Option<Auth>,
 
+    /// Authentication scheme for --auth [default: basic, or bearer for `bearer:TOKEN` and JWTs;
+    /// `auto` also recognises common token prefixes like ghp_ and glpat-]
+    #[arg(short = 'A', long = "auth-type", value_parser = AuthType::parser())]
+    pub auth_type: Option<AuthType>,
+
+    /// AWS region for --auth-type aws-sigv4 (default: from the host or the AWS config)
+    #[arg(long = "aws-region", value_name = "REGION")]
+    pub aws_region: Option<String>,
+
+    /// AWS service for --auth-type aws-sigv4, e.g. s3, execute-api, es
+    #[arg(long = "aws-service", value_name = "SERVICE")]
+    pub aws_service: Option<String>,
+
+    /// Profile in ~/.aws/credentials for --auth-type aws-sigv4 [default: $AWS_PROFILE or default]
+    #[arg(long = "aws-profile", value_name = "PROFILE")]
+    pub aws_profile: Option<String>,
+
+    /// OAuth2 token endpoint for --auth-type oauth2 (client credentials via --auth ID:SECRET)
+    #[arg(long = "oauth-token-url", value_name = "URL")]
+    pub oauth_token_url: Option<String>,
+
+    /// OAuth2 grant [default: client-credentials]
+    #[arg(long = "oauth-grant", value_enum)]
+    pub oauth_grant: Option<Grant>,
+
+    /// OAuth2 scope to request
+    #[arg(long = "oauth-scope", value_name = "SCOPE")]
+    pub oauth_scope: Option<String>,
+
+    /// Resource owner credentials for the password grant
+    #[arg(long = "oauth-user", value_name = "USER:PASSWORD")]
+    pub oauth_user: Option<String>,
+
+    /// Refresh token for the refresh-token grant
+    #[arg(long = "oauth-refresh-token", value_name = "TOKEN")]
+    pub oauth_refresh_token: Option<String>,
+
+    /// String to sign for --auth-type hmac, with placeholders like {method}, {path},
+    /// {timestamp}, {body}, {header:NAME} (which sees the default headers but not
+    /// cookies) [default: {method}\n{path}\n{timestamp}\n{body}]
+    #[arg(long = "hmac-template", value_name = "TEMPLATE")]
+    pub hmac_template: Option<String>,
+
+    /// Header for --auth-type hmac, e.g. 'X-Signature:sha256={signature}' (repeatable)
+    /// [default: X-Timestamp:{timestamp} and X-Signature:{signature}]
+    #[arg(long = "hmac-header", value_name = "NAME:VALUE")]
+    pub hmac_headers: Vec<String>,
+
+    /// Encoding of the HMAC signature [default: hex]
+    #[arg(long = "hmac-encoding", value_enum)]
+    pub hmac_encoding: Option<Encoding>,
+
+    /// Command that prints the secret (or the whole --auth value), like a git credential helper
+    #[arg(long = "auth-cmd", value_name = "COMMAND")]
+    pub auth_cmd: Option<String>,
+
     /// Verbose mode: print request details
     #[arg(short = 'v', long = "verbose")]
     pub verbose: bool,
@@ -124,6 +289,102 @@ pub struct RequestArgs {
     /// Output file path
     #[arg(short = 'o', long = "output")]
     pub output: Option<String>,
+
+    /// Send body fields as an urlencoded form (multipart when files are present)
+    #[arg(short = 'f', long = "form")]
+    pub form: bool,
+
+    /// Raw request body, sent as-is
+    #[arg(long = "raw", value_name = "DATA")]
+    pub raw: Option<String>,
+
+    /// Do not read the request body from stdin
+    #[arg(short = 'I', long = "ignore-stdin")]
+    pub ignore_stdin: bool,
+
+    /// Scheme used when the URL has none [default: http, or https when invoked as `https`]
+    #[arg(long = "default-scheme", value_parser = ["http", "https"])]
+    pub default_scheme: Option<String>,
+
+    /// Add or override a default header sent with every request ('Header:' drops it)
+    #[arg(long = "default-header", value_name = "HEADER", value_parser = parse_default_header)]
+    pub default_headers: Vec<(String, String)>,
+
+    /// Named session (or session file path) for cookies, headers and auth;
+    /// Basic, Digest and Bearer credentials are saved, OAuth2, HMAC and AWS
+    /// secrets are not
+    #[arg(long = "session", value_name = "NAME", conflicts_with = "session_read_only")]
+    pub session: Option<String>,
+
+    /// Use a session without updating it
+    #[arg(long = "session-read-only", value_name = "NAME")]
+    pub session_read_only: Option<String>,
+
+    /// Netscape-format cookie file to read cookies from and save them to
+    #[arg(long = "cookie-jar", value_name = "FILE")]
+    pub cookie_jar: Option<String>,
+
+    /// Do not read credentials from ~/.netrc (or $NETRC)
+    #[arg(long = "ignore-netrc")]
+    pub ignore_netrc: bool,
+
+    /// Verify the server certificate: yes, no, or the path of a CA bundle to trust instead
+    /// of the system's [default: yes]
+    #[arg(long = "verify", value_name = "yes|no|CA_BUNDLE")]
+    pub verify: Option<Verify>,
+
+    /// Minimum TLS version [default: tls1.2]
+    #[arg(long = "ssl", value_enum)]
+    pub ssl: Option<TlsVersion>,
+
+    /// Client certificate (PEM) for mutual TLS; may also hold the private key
+    #[arg(long = "cert", value_name = "FILE", conflicts_with = "cert_p12")]
+    pub cert: Option<String>,
+
+    /// Private key (PEM) for --cert
+    #[arg(long = "cert-key", value_name = "FILE", requires = "cert")]
+    pub cert_key: Option<String>,
+
+    /// Client certificate and key as PKCS#12 (the passphrase is prompted for,
+    /// or read from $HTTPRS_CERT_PASSWORD)
+    #[arg(long = "cert-p12", value_name = "FILE")]
+    pub cert_p12: Option<String>,
+
+    /// Only trust the server if a certificate in its chain has this public
+    /// key (sha256//BASE64 as in curl; several may be separated by ';')
+    #[arg(long = "pin", value_name = "sha256//BASE64")]
+    pub pins: Vec<String>,
+
+    /// Proxy for SCHEME (http, https or all) requests, e.g.
+    /// `https:http://proxy:3128` or `all:socks5h://proxy:1080`; repeatable
+    #[arg(long = "proxy", value_name = "SCHEME:URL")]
+    pub proxies: Vec<String>,
+
+    /// Credentials for the proxy (the password is prompted for if missing)
+    #[arg(long = "proxy-auth", value_name = "USER[:PASSWORD]")]
+    pub proxy_auth: Option<String>,
+
+    /// Comma separated hosts to reach without a proxy; replaces $NO_PROXY
+    #[arg(long = "no-proxy", value_name = "HOSTS")]
+    pub no_proxy: Option<String>,
+
+    /// Ignore HTTP_PROXY, HTTPS_PROXY, ALL_PROXY and NO_PROXY
+    #[arg(long = "ignore-proxy-env")]
+    pub ignore_proxy_env: bool,
+
+    /// Print the server certificate chain and the negotiated TLS version; the
+    /// chain comes from a separate probe connection made with the same settings
+    #[arg(long = "print-cert")]
+    pub print_cert: bool,
+
+    /// Print the negotiated TLS version, cipher suite and ALPN, from a
+    /// separate probe connection made with the same settings
+    #[arg(long = "tls-info")]
+    pub tls_info: bool,
+
+    /// How repeated JSON body keys are handled
+    #[arg(long = "duplicate-keys", value_enum, default_value_t = DuplicateKeys::Array)]
+    pub duplicate_keys: DuplicateKeys,
 }
 
 impl RequestArgs {
@@ -147,69 +408,231 @@ pub enum OutputFilter {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum RequestItem {
-    //HTTP Header: "Authorization:Bearer token"
+    //HTTP Header: "Authorization:Bearer token", "X-Signature:@sig.txt"
+    //An empty value ("Header:") unsets the header, including httprs defaults
     Header(String, String),
+    //Explicitly empty HTTP Header: "Header;"
+    EmptyHeader(String),
     //Query Parameter: "page==1"
     QueryParam(String, String),
-    //JSON Body field: "name=alice"
+    //JSON Body field: "name=alice", "description=@notes.txt"
     Body(String, String),
+    //Raw JSON Body field: "age:=30", "active:=true", "config:=@config.json"
+    RawJson(String, serde_json::Value),
     //file upload: key@filepath
     FormFile(String, String),
+    //raw request body from file: @payload.json
+    RawBodyFile(String),
+    //Ad-hoc cookie for this request: "session~=abc123"
+    Cookie(String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Separator {
+    Header,
+    EmptyHeader,
+    HeaderFile,
+    QueryParam,
+    Body,
+    BodyFile,
+    RawJson,
+    RawJsonFile,
+    FormFile,
+    Cookie,
+}
+
+/// Item separators and their precedence.
+///
+/// The earliest unescaped separator in the item wins; when several start at
+/// the same position the longest one wins:
+///
+/// | Separator | Item            | Example                |
+/// |-----------|-----------------|------------------------|
+/// | `:=@`     | raw JSON file   | `config:=@config.json` |
+/// | `:=`      | raw JSON        | `age:=30`              |
+/// | `==`      | query parameter | `page==1`              |
+/// | `=@`      | body from file  | `notes=@notes.txt`     |
+/// | `:@`      | header file     | `X-Sig:@sig.txt`       |
+/// | `:`       | header          | `Accept:text/html`     |
+/// | `;`       | empty header    | `X-Empty;`             |
+/// | `~=`      | cookie          | `session~=abc123`      |
+/// | `=`       | body field      | `name=alice`           |
+/// | `@`       | file upload     | `photo@image.jpg`      |
+///
+/// A backslash escapes a separator character in the key (`a\:b=c`,
+/// `key\==value`); `\\` is a literal backslash. Values are taken verbatim,
+/// except that a leading `\@` stands for a literal `@`.
+const SEPARATORS: &[(&str, Separator)] = &[
+    (":=@", Separator::RawJsonFile),
+    (":=", Separator::RawJson),
+    ("==", Separator::QueryParam),
+    ("=@", Separator::BodyFile),
+    (":@", Separator::HeaderFile),
+    (":", Separator::Header),
+    (";", Separator::EmptyHeader),
+    ("~=", Separator::Cookie),
+    ("=", Separator::Body),
+    ("@", Separator::FormFile),
+];
+
+/// Characters that a backslash escapes in item keys.
+const ESCAPABLE: &[char] = &[':', ';', '=', '@', '~', '\\'];
+
+fn find_separator(s: &str) -> Option<(usize, &'static str, Separator)> {
+    let mut escaped = false;
+    for (pos, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if c == '\\' {
+            escaped = true;
+            continue;
+        }
+        if let Some(&(token, sep)) = SEPARATORS.iter().find(|(token, _)| s[pos..].starts_with(token)) {
+            return Some((pos, token, sep));
+        }
+    }
+    None
+}
+
+/// Removes backslash escapes in front of `chars`; other backslashes are kept.
+fn unescape(s: &str, chars: &[char]) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut iter = s.chars().peekable();
+    while let Some(c) = iter.next() {
+        if c == '\\'
+            && let Some(&next) = iter.peek().filter(|next| chars.contains(next))
+        {
+            out.push(next);
+            iter.next();
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Reads the contents of a `key=@file`, `key:=@file` or `Header:@file` item.
+fn read_item_file(path: &str) -> Result<String> {
+    if path.is_empty() {
+        return Err(anyhow!("File path cannot be empty"));
+    }
+    std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read file '{}': {}", path, e))
+}
+
+/// `key=\@text` sends a literal leading `@` instead of reading a file.
+fn unescape_leading_at(value: String) -> String {
+    match value.strip_prefix("\\@") {
+        Some(rest) => format!("@{}", rest),
+        None => value,
+    }
 }
 
 impl FromStr for RequestItem {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let Some((pos, token, separator)) = find_separator(s) else {
+            return Err(anyhow!(
+                "Invalid format: '{}'. Expected 'Header:Value','key@file', 'key==value', 'key=value', 'key:=json' or 'name~=value'",
+                s
+            ));
+        };
+
+        // Body keys keep `\\`, `\[` and `\]` escapes for the nested path parser
+        let raw_key = s[..pos].trim();
+        let key = match separator {
+            Separator::Body | Separator::BodyFile | Separator::RawJson | Separator::RawJsonFile => {
+                unescape(raw_key, &[':', ';', '=', '@', '~'])
+            }
+            _ => unescape(raw_key, ESCAPABLE),
+        };
+        let value = s[pos + token.len()..].trim().to_string();
 
-        if let Some(pos) = s.find('@') {
-            let before_at=&s[..pos];
-            if !before_at.contains('=')&&!before_at.contains(':'){
-                let key=before_at.trim().to_string();
-                let filepath = s[pos + 1..].trim().to_string();
+        match separator {
+            Separator::FormFile if pos == 0 => {
+                if value.is_empty() {
+                    return Err(anyhow!("File path cannot be empty: {}", s));
+                }
+                Ok(RequestItem::RawBodyFile(value))
+            }
+            Separator::FormFile => {
                 if key.is_empty() {
                     return Err(anyhow!("Form file key cannot be empty: {}", s));
                 }
-                if filepath.is_empty() {
+                if value.is_empty() {
                     return Err(anyhow!("File path cannot be empty: {}", s));
                 }
-                return Ok(RequestItem::FormFile(key, filepath));
+                Ok(RequestItem::FormFile(key, value))
             }
-        }
-
-        //find operator is more efficient than split , it doesn't create iterator
-        if let Some(pos) = s.find(':') {
-            let key = s[..pos].trim().to_string();
-            let value = s[pos + 1..].trim().to_string();
-            if key.is_empty() {
-                return Err(anyhow!("Header key cannot be empty: {}", s));
+            Separator::Header | Separator::HeaderFile => {
+                if key.is_empty() {
+                    return Err(anyhow!("Header key cannot be empty: {}", s));
+                }
+                let value = if separator == Separator::HeaderFile {
+                    read_item_file(&value)?.trim().to_string()
+                } else {
+                    unescape_leading_at(value)
+                };
+                Ok(RequestItem::Header(key, value))
             }
-            return Ok(RequestItem::Header(key, value));
-        }
-
-
-        //Longest Match First
-        if let Some(pos) = s.find("==") {
-            let key = s[..pos].trim().to_string();
-            let value = s[pos + 2..].trim().to_string();
-            if key.is_empty() {
-                return Err(anyhow!("Query parameter key cannot be empty: {}", s));
+            Separator::EmptyHeader => {
+                if key.is_empty() {
+                    return Err(anyhow!("Header key cannot be empty: {}", s));
+                }
+                if !value.is_empty() {
+                    return Err(anyhow!(
+                        "Unexpected value after ';' in '{}'. Use 'Header:Value' to send a value",
+                        s
+                    ));
+                }
+                Ok(RequestItem::EmptyHeader(key))
             }
-            return Ok(RequestItem::QueryParam(key, value));
-        }
-
-        if let Some(pos) = s.find('=') {
-            let key = s[..pos].trim().to_string();
-            let value = s[pos + 1..].trim().to_string();
-            if key.is_empty() {
-                return Err(anyhow!("Body key cannot be empty: {}", s));
+            Separator::Cookie => {
+                if key.is_empty() {
+                    return Err(anyhow!("Cookie name cannot be empty: {}", s));
+                }
+                if key.contains(|c: char| c == ';' || c == ',' || c.is_whitespace())
+                    || value.contains(|c: char| c == ';' || c.is_control())
+                {
+                    return Err(anyhow!("Invalid cookie: '{}'", s));
+                }
+                Ok(RequestItem::Cookie(key, value))
+            }
+            Separator::QueryParam => {
+                if key.is_empty() {
+                    return Err(anyhow!("Query parameter key cannot be empty: {}", s));
+                }
+                Ok(RequestItem::QueryParam(key, value))
+            }
+            Separator::Body | Separator::BodyFile => {
+                if key.is_empty() {
+                    return Err(anyhow!("Body key cannot be empty: {}", s));
+                }
+                parse_path(&key)?;
+                let value = if separator == Separator::BodyFile {
+                    read_item_file(&value)?
+                } else {
+                    unescape_leading_at(value)
+                };
+                Ok(RequestItem::Body(key, value))
+            }
+            Separator::RawJson | Separator::RawJsonFile => {
+                if key.is_empty() {
+                    return Err(anyhow!("JSON field key cannot be empty: {}", s));
+                }
+                parse_path(&key)?;
+                let value = if separator == Separator::RawJsonFile {
+                    read_item_file(&value)?
+                } else {
+                    value
+                };
+                let json = serde_json::from_str(&value)
+                    .map_err(|e| anyhow!("Invalid JSON value for '{}': {}", key, e))?;
+                Ok(RequestItem::RawJson(key, json))
             }
-            return Ok(RequestItem::Body(key, value));
         }
-        Err(anyhow!(
-            "Invalid format: '{}'. Expected 'Header:Value','key@file', 'key==value', or 'key=value'",
-            s
-        ))
     }
 }
 
@@ -222,10 +645,51 @@ fn parse_url(s: &str) -> Result<String> {
     Ok(s.into())
 }
 
+/// Expands URL shorthands and validates the result.
+///
+/// `:3000/api` -> `<default_scheme>://localhost:3000/api`
+/// `:/health` -> `<default_scheme>://localhost/health`
+/// `example.com/api` -> `<default_scheme>://example.com/api`
+pub fn expand_url(s: &str, default_scheme: &str) -> Result<String> {
+    let expanded = if let Some(rest) = s.strip_prefix(':') {
+        if rest.is_empty() || rest.starts_with('/') {
+            format!("{}://localhost{}", default_scheme, rest)
+        } else {
+            format!("{}://localhost:{}", default_scheme, rest)
+        }
+    } else if has_scheme(s) {
+        s.to_string()
+    } else {
+        format!("{}://{}", default_scheme, s)
+    };
+    parse_url(&expanded).map_err(|e| anyhow!("Invalid URL '{}': {}", s, e))
+}
+
+fn has_scheme(s: &str) -> bool {
+    s.split_once("://").is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+fn parse_method(s: &str) -> Result<reqwest::Method> {
+    reqwest::Method::from_bytes(s.to_ascii_uppercase().as_bytes())
+        .map_err(|_| anyhow!("Invalid HTTP method: {}", s))
+}
+
 fn parse_request_item(s: &str) -> Result<RequestItem> {
     s.parse()
 }
 
+fn parse_default_header(s: &str) -> Result<(String, String)> {
+    match s.parse()? {
+        RequestItem::Header(name, value) => Ok((name, value)),
+        _ => Err(anyhow!("Invalid default header '{}'. Expected 'Header:Value' or 'Header:'", s)),
+    }
+}
+
 fn parse_auth(s: &str) -> Result<Auth> {
     s.parse()
 }
@@ -245,6 +709,41 @@ mod tests {
         assert!(parse_url("https://httpbin.org/post").is_ok());
     }
 
+    #[test]
+    fn expand_url_localhost_shorthand() {
+        assert_eq!(expand_url(":8080/health", "http").unwrap(), "http://localhost:8080/health");
+        assert_eq!(expand_url(":3000", "https").unwrap(), "https://localhost:3000");
+        assert_eq!(expand_url(":/api/users", "http").unwrap(), "http://localhost/api/users");
+        assert_eq!(expand_url(":", "http").unwrap(), "http://localhost");
+    }
+
+    #[test]
+    fn expand_url_default_scheme() {
+        assert_eq!(
+            expand_url("localhost:3000/api", "http").unwrap(),
+            "http://localhost:3000/api"
+        );
+        assert_eq!(
+            expand_url("example.com/users", "https").unwrap(),
+            "https://example.com/users"
+        );
+        // An explicit scheme is kept
+        assert_eq!(
+            expand_url("https://example.com", "http").unwrap(),
+            "https://example.com"
+        );
+        assert_eq!(
+            expand_url("http://example.com", "https").unwrap(),
+            "http://example.com"
+        );
+    }
+
+    #[test]
+    fn expand_url_errors() {
+        assert!(expand_url(":notaport", "http").is_err());
+        assert!(expand_url("http://", "http").is_err());
+    }
+
     #[test]
     fn parse_request_item_works() {
         assert_eq!(
@@ -302,82 +801,88 @@ mod tests {
     }
 
 
+    fn parse_args(argv: &[&str]) -> RequestArgs {
+        RequestArgs::try_parse_from(argv).unwrap()
+    }
+
     #[test]
     fn output_filter_works() {
-        let args = RequestArgs {
-            url: "http://example.com".to_string(),
-            items: vec![],
-            auth: None,
-            verbose: false,
-            timeout: 30,
-            follow_redirects: false,
-            max_redirects: 10,
-            headers_only: true,
-            body_only: false,
-            download: false,
-            output: None,
-        };
+        let args = parse_args(&["httprs", "http://example.com", "--headers"]);
 
         assert_eq!(args.output_filter(), OutputFilter::HeadersOnly);
     }
 
     #[test]
     fn output_filter_body_only() {
-        let args = RequestArgs {
-            url: "http://example.com".to_string(),
-            items: vec![],
-            auth: None,
-            verbose: false,
-            timeout: 30,
-            follow_redirects: false,
-            max_redirects: 10,
-            headers_only: false,
-            body_only: true,
-            download: false,
-            output: None,
-        };
+        let args = parse_args(&["httprs", "http://example.com", "--body"]);
 
         assert_eq!(args.output_filter(), OutputFilter::BodyOnly);
     }
 
     #[test]
     fn output_filter_all() {
-        let args = RequestArgs {
-            url: "http://example.com".to_string(),
-            items: vec![],
-            auth: None,
-            verbose: false,
-            timeout: 30,
-            follow_redirects: false,
-            max_redirects: 10,
-            headers_only: false,
-            body_only: false,
-            download: false,
-            output: None,
-        };
+        let args = parse_args(&["httprs", "http://example.com"]);
 
         assert_eq!(args.output_filter(), OutputFilter::All);
     }
 
     #[test]
     fn command_method_works() {
-        let get_cmd = Command::Get(RequestArgs {
-            url: "http://example.com".to_string(),
-            items: vec![],
-            auth: None,
-            verbose: false,
-            timeout: 30,
-            follow_redirects: false,
-            max_redirects: 10,
-            headers_only: false,
-            body_only: false,
-            download: false,
-            output: None,
-        });
+        let get_cmd = Command::Get(parse_args(&["httprs", "http://example.com"]));
 
         assert_eq!(get_cmd.method(), reqwest::Method::GET);
     }
 
+    #[test]
+    fn custom_method_works() {
+        let cli = Cli::try_parse_from(["httprs", "request", "PURGE", "http://example.com/a.js"]).unwrap();
+        assert_eq!(cli.command.method().as_str(), "PURGE");
+        assert_eq!(cli.command.args().url, "http://example.com/a.js");
+
+        let cli = Cli::try_parse_from([
+            "httprs",
+            "request",
+            "propfind",
+            "http://example.com/dav/",
+            "Depth:1",
+            "-v",
+        ])
+        .unwrap();
+        assert_eq!(cli.command.method().as_str(), "PROPFIND");
+        assert!(cli.command.args().verbose);
+        assert_eq!(
+            cli.command.args().items,
+            vec![RequestItem::Header("Depth".into(), "1".into())]
+        );
+    }
+
+    #[test]
+    fn custom_method_invalid() {
+        assert!(parse_method("PURGE").is_ok());
+        assert!(parse_method("BAD METHOD").is_err());
+        assert!(parse_method("").is_err());
+    }
+
+    #[test]
+    fn expand_method_shorthand_works() {
+        let expand = |args: &[&str]| {
+            expand_method_shorthand(args.iter().map(|s| s.to_string()))
+        };
+
+        assert_eq!(
+            expand(&["httprs", "MKCOL", "http://example.com/dav/new/"]),
+            vec!["httprs", "request", "MKCOL", "http://example.com/dav/new/"]
+        );
+
+        // Regular subcommands and flags are left alone
+        assert_eq!(
+            expand(&["httprs", "get", "http://example.com"]),
+            vec!["httprs", "get", "http://example.com"]
+        );
+        assert_eq!(expand(&["httprs", "--help"]), vec!["httprs", "--help"]);
+        assert_eq!(expand(&["httprs"]), vec!["httprs"]);
+    }
+
     #[test]
     fn parse_header_with_spaces() {
         assert_eq!(
@@ -406,6 +911,65 @@ mod tests {
             parse_request_item("Header:").unwrap(),
             RequestItem::Header("Header".into(), "".into())
         );
+
+        assert_eq!(
+            parse_request_item("Header;").unwrap(),
+            RequestItem::EmptyHeader("Header".into())
+        );
+    }
+
+    #[test]
+    fn parse_empty_header_errors() {
+        assert!(parse_request_item(";").is_err());
+        assert!(parse_request_item("Header;value").is_err());
+
+        // ';' after another separator is part of the value
+        assert_eq!(
+            parse_request_item("Cookie:a=1;b=2").unwrap(),
+            RequestItem::Header("Cookie".into(), "a=1;b=2".into())
+        );
+        assert_eq!(
+            parse_request_item("X\\;Y=1").unwrap(),
+            RequestItem::Body("X;Y".into(), "1".into())
+        );
+    }
+
+    #[test]
+    fn parse_cookie_item() {
+        assert_eq!(
+            parse_request_item("session~=abc123").unwrap(),
+            RequestItem::Cookie("session".into(), "abc123".into())
+        );
+        assert_eq!(
+            parse_request_item("empty~=").unwrap(),
+            RequestItem::Cookie("empty".into(), "".into())
+        );
+        assert!(parse_request_item("~=abc").is_err());
+        assert!(parse_request_item("a b~=1").is_err());
+        assert!(parse_request_item("a~=1;b=2").is_err());
+
+        // `\~` keeps a tilde in other keys
+        assert_eq!(
+            parse_request_item("a\\~=b").unwrap(),
+            RequestItem::Body("a~".into(), "b".into())
+        );
+        assert_eq!(
+            parse_request_item("a~b=c").unwrap(),
+            RequestItem::Body("a~b".into(), "c".into())
+        );
+    }
+
+    #[test]
+    fn parse_default_header_works() {
+        assert_eq!(
+            parse_default_header("User-Agent:curl/8.0").unwrap(),
+            ("User-Agent".into(), "curl/8.0".into())
+        );
+        assert_eq!(
+            parse_default_header("X-Powered-By:").unwrap(),
+            ("X-Powered-By".into(), "".into())
+        );
+        assert!(parse_default_header("name=alice").is_err());
     }
     #[test]
     fn parse_email_address_in_body() {
@@ -467,10 +1031,10 @@ mod tests {
 
     #[test]
     fn parse_value_with_multiple_at_signs() {
-        // ✅ 值中可能有多个 @
+        // ✅ 值中可能有多个 @ (a leading @ reads a file, so escape it)
         assert_eq!(
 
-            parse_request_item("mentions=@user1,@user2,@user3").unwrap(),
+            parse_request_item("mentions=\\@user1,@user2,@user3").unwrap(),
             RequestItem::Body("mentions".into(),
                               "@user1,@user2,@user3".into())
         );
@@ -487,10 +1051,219 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_raw_json_items() {
+        assert_eq!(
+            parse_request_item("count:=42").unwrap(),
+            RequestItem::RawJson("count".into(), serde_json::json!(42))
+        );
+
+        assert_eq!(
+            parse_request_item("active:=true").unwrap(),
+            RequestItem::RawJson("active".into(), serde_json::json!(true))
+        );
+
+        assert_eq!(
+            parse_request_item(r#"tags:=["a","b"]"#).unwrap(),
+            RequestItem::RawJson("tags".into(), serde_json::json!(["a", "b"]))
+        );
+
+        assert_eq!(
+            parse_request_item(r#"meta:={"nested": {"x": null}}"#).unwrap(),
+            RequestItem::RawJson("meta".into(), serde_json::json!({"nested": {"x": null}}))
+        );
+    }
+
+    #[test]
+    fn parse_raw_json_errors() {
+        assert!(parse_request_item("count:=").is_err());
+        assert!(parse_request_item("count:=forty-two").is_err());
+        assert!(parse_request_item(":=42").is_err());
+    }
+
+    #[test]
+    fn parse_nested_body_keys() {
+        assert_eq!(
+            parse_request_item("user[name]=alice").unwrap(),
+            RequestItem::Body("user[name]".into(), "alice".into())
+        );
+
+        assert_eq!(
+            parse_request_item("items[0][id]:=7").unwrap(),
+            RequestItem::RawJson("items[0][id]".into(), serde_json::json!(7))
+        );
+
+        assert!(parse_request_item("user[name=alice").is_err());
+        assert!(parse_request_item("user]=alice").is_err());
+    }
+
+    #[test]
+    fn parse_earliest_separator_wins() {
+        // ':' inside a body value must not turn the item into a header
+        assert_eq!(
+            parse_request_item("redirect=http://example.com").unwrap(),
+            RequestItem::Body("redirect".into(), "http://example.com".into())
+        );
+
+        // '=' inside a header value must not turn the item into a body field
+        assert_eq!(
+            parse_request_item("Prefer:return=minimal").unwrap(),
+            RequestItem::Header("Prefer".into(), "return=minimal".into())
+        );
+
+        assert_eq!(
+            parse_request_item("filter==a:b").unwrap(),
+            RequestItem::QueryParam("filter".into(), "a:b".into())
+        );
+    }
+
+    fn write_temp_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("httprs-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn parse_values_from_files() {
+        let notes = write_temp_file("notes.txt", "line one\nline two\n");
+        assert_eq!(
+            parse_request_item(&format!("description=@{}", notes)).unwrap(),
+            RequestItem::Body("description".into(), "line one\nline two\n".into())
+        );
+
+        let config = write_temp_file("config.json", r#"{"retries": 3, "debug": false}"#);
+        assert_eq!(
+            parse_request_item(&format!("config:=@{}", config)).unwrap(),
+            RequestItem::RawJson("config".into(), serde_json::json!({"retries": 3, "debug": false}))
+        );
+
+        let sig = write_temp_file("sig.txt", "abc123\n");
+        assert_eq!(
+            parse_request_item(&format!("X-Signature:@{}", sig)).unwrap(),
+            RequestItem::Header("X-Signature".into(), "abc123".into())
+        );
+    }
+
+    #[test]
+    fn parse_values_from_files_errors() {
+        assert!(parse_request_item("description=@/nonexistent/httprs/notes.txt").is_err());
+        assert!(parse_request_item("description=@").is_err());
+
+        let invalid = write_temp_file("invalid.json", "{not json");
+        assert!(parse_request_item(&format!("config:=@{}", invalid)).is_err());
+    }
+
+    #[test]
+    fn parse_escaped_leading_at() {
+        assert_eq!(
+            parse_request_item("handle=\\@alice").unwrap(),
+            RequestItem::Body("handle".into(), "@alice".into())
+        );
+
+        assert_eq!(
+            parse_request_item("X-Handle:\\@alice").unwrap(),
+            RequestItem::Header("X-Handle".into(), "@alice".into())
+        );
+    }
+
+    #[test]
+    fn parse_separator_precedence_table() {
+        use RequestItem::*;
+        let cases: Vec<(&str, RequestItem)> = vec![
+            // earliest separator wins
+            ("a:b=c", Header("a".into(), "b=c".into())),
+            ("a=b:c", Body("a".into(), "b:c".into())),
+            ("a==b=c", QueryParam("a".into(), "b=c".into())),
+            ("a=b==c", Body("a".into(), "b==c".into())),
+            ("a:b:=1", Header("a".into(), "b:=1".into())),
+            ("a:=1", RawJson("a".into(), serde_json::json!(1))),
+            ("a=:1", Body("a".into(), ":1".into())),
+            ("a:b==c", Header("a".into(), "b==c".into())),
+            ("a==b:c", QueryParam("a".into(), "b:c".into())),
+            ("a@b=c", FormFile("a".into(), "b=c".into())),
+            ("a=b@c", Body("a".into(), "b@c".into())),
+            ("a:b@c", Header("a".into(), "b@c".into())),
+            ("a==b@c", QueryParam("a".into(), "b@c".into())),
+            ("a@b:c", FormFile("a".into(), "b:c".into())),
+            ("a:=\"b=c\"", RawJson("a".into(), serde_json::json!("b=c"))),
+            // longest separator at the same position wins
+            ("a===b", QueryParam("a".into(), "=b".into())),
+            ("a:=[1]", RawJson("a".into(), serde_json::json!([1]))),
+            ("a==", QueryParam("a".into(), "".into())),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_request_item(input).ok(), Some(expected), "item: {}", input);
+        }
+
+        // `:=` wins over `:`, so the value must be valid JSON
+        assert!(parse_request_item("a:==1").is_err());
+    }
+
+    #[test]
+    fn parse_escaped_separators() {
+        assert_eq!(
+            parse_request_item("a\\:b=c").unwrap(),
+            RequestItem::Body("a:b".into(), "c".into())
+        );
+        assert_eq!(
+            parse_request_item("key\\==value").unwrap(),
+            RequestItem::Body("key=".into(), "value".into())
+        );
+        assert_eq!(
+            parse_request_item("a\\=b==c").unwrap(),
+            RequestItem::QueryParam("a=b".into(), "c".into())
+        );
+        assert_eq!(
+            parse_request_item("urn\\:isbn\\:123==x").unwrap(),
+            RequestItem::QueryParam("urn:isbn:123".into(), "x".into())
+        );
+        assert_eq!(
+            parse_request_item("user\\@host==1").unwrap(),
+            RequestItem::QueryParam("user@host".into(), "1".into())
+        );
+        assert_eq!(
+            parse_request_item("a\\@b=c").unwrap(),
+            RequestItem::Body("a@b".into(), "c".into())
+        );
+        assert_eq!(
+            parse_request_item("X-A\\:B:v").unwrap(),
+            RequestItem::Header("X-A:B".into(), "v".into())
+        );
+        assert_eq!(
+            parse_request_item("photo\\=1@img.jpg").unwrap(),
+            RequestItem::FormFile("photo=1".into(), "img.jpg".into())
+        );
+        // escaped backslash before a separator
+        assert_eq!(
+            parse_request_item("dir\\\\==x").unwrap(),
+            RequestItem::QueryParam("dir\\".into(), "x".into())
+        );
+        // an escaped separator alone is not a separator
+        assert!(parse_request_item("a\\:b").is_err());
+        // values are verbatim
+        assert_eq!(
+            parse_request_item("path=C:\\dir\\=x").unwrap(),
+            RequestItem::Body("path".into(), "C:\\dir\\=x".into())
+        );
+    }
+
+    #[test]
+    fn parse_raw_body_file() {
+        assert_eq!(
+            parse_request_item("@payload.json").unwrap(),
+            RequestItem::RawBodyFile("payload.json".into())
+        );
+
+        assert_eq!(
+            parse_request_item("@/tmp/data@2024.bin").unwrap(),
+            RequestItem::RawBodyFile("/tmp/data@2024.bin".into())
+        );
+    }
+
     #[test]
     fn parse_errors_on_invalid_file() {
         // ❌ 无效的文件上传格式
-        assert!(parse_request_item("@no-key").is_err());
+        assert!(parse_request_item("@").is_err());
         assert!(parse_request_item("key@").is_err());
     }
 
@@ -1,11 +1,21 @@
 use anyhow::Result;
 use reqwest::{header, Client};
+use reqwest_cookie_store::CookieStoreMutex;
+use rustls::ClientConfig;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::cli::RequestArgs;
+use crate::cli::{RequestArgs, RequestItem};
+use crate::cookies::RequestCookies;
+use crate::proxy::Proxies;
+use crate::tls;
 
-
-pub fn build_client(args:&RequestArgs)->Result<Client>{
+/// Headers sent with every request.
+///
+/// Starts from httprs' built-in defaults, applies `--default-header`
+/// overrides (an empty value drops the header), then drops any header
+/// unset with a `Header:` request item.
+pub fn default_headers(args:&RequestArgs)->Result<header::HeaderMap>{
     let mut headers=header::HeaderMap::new();
 
     let user_agent=format!("httprs/{}", env!("CARGO_PKG_VERSION"));
@@ -13,9 +23,52 @@ pub fn build_client(args:&RequestArgs)->Result<Client>{
 
     headers.insert("X-Powered-By",header::HeaderValue::from_static("Rust"),);
 
+    for (name, value) in &args.default_headers {
+        let name = header::HeaderName::from_bytes(name.as_bytes())?;
+        if value.is_empty() {
+            headers.remove(&name);
+        } else {
+            headers.insert(name, header::HeaderValue::from_str(value)?);
+        }
+    }
+
+    for item in &args.items {
+        if let RequestItem::Header(name, value) = item
+            && value.is_empty()
+        {
+            headers.remove(name.as_str());
+        }
+    }
+    Ok(headers)
+}
+
+/// Builds the HTTP client. `cookie_jar` (e.g. from a session) stores cookies
+/// set by responses and sends them with matching requests; `name~=value`
+/// cookie items are sent on top of it. `tls_config` comes from
+/// [`tls::client_config`], built once so the `--tls-info`
+/// and `--print-cert` probes can reuse it.
+pub fn build_client(
+    args: &RequestArgs,
+    cookie_jar: Option<Arc<CookieStoreMutex>>,
+    tls_config: ClientConfig,
+) -> Result<Client> {
     let mut client_builder = Client::builder()
-        .default_headers(headers)
-        .timeout(Duration::from_secs(args.timeout));
+        .default_headers(default_headers(args)?)
+        .timeout(Duration::from_secs(args.timeout))
+        .use_preconfigured_tls(tls_config)
+        .proxy(Proxies::from_args(args)?.into_reqwest());
+
+    let has_cookie_items = args.items.iter().any(|item| matches!(item, RequestItem::Cookie(_, _)));
+    let cookie_jar = match cookie_jar {
+        None if has_cookie_items => Some(Arc::new(CookieStoreMutex::default())),
+        jar => jar,
+    };
+    if let Some(jar) = cookie_jar {
+        client_builder = match RequestCookies::from_args(args, jar.clone()) {
+            Some(cookies) => client_builder.cookie_provider(Arc::new(cookies)),
+            None => client_builder.cookie_provider(jar),
+        };
+    }
 
     if args.follow_redirects{
         client_builder = client_builder.redirect(reqwest::redirect::Policy::limited(args.max_redirects));
@@ -27,47 +80,117 @@ pub fn build_client(args:&RequestArgs)->Result<Client>{
 
 }
 
+/// Builds a client for requests made on the user's behalf to another host,
+/// such as the OAuth2 token endpoint. Only `--verify`, the proxy and the
+/// timeout carry over: the request's default headers, cookies, client
+/// certificate and `--pin` belong to the target host.
+pub fn build_plain_client(args: &RequestArgs) -> Result<Client> {
+    let plain = RequestArgs {
+        ssl: None,
+        cert: None,
+        cert_key: None,
+        cert_p12: None,
+        pins: vec![],
+        ..args.clone()
+    };
+    Ok(Client::builder()
+        .timeout(Duration::from_secs(args.timeout))
+        .use_preconfigured_tls(tls::client_config(&plain)?)
+        .proxy(Proxies::from_args(args)?.into_reqwest())
+        .build()?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_build_client_basic() {
-        let args = RequestArgs {
-            url: "http://example.com".to_string(),
-            items: vec![],
-            auth: None,
-            verbose: false,
-            timeout: 30,
-            follow_redirects: false,
-            max_redirects: 10,
-            headers_only: false,
-            body_only: false,
-            download: false,
-            output: None,
-        };
+        let args = parse_args(&["httprs", "http://example.com"]);
 
-        let client = build_client(&args);
+        let client = build_client(&args, None, tls::client_config(&args).unwrap());
         assert!(client.is_ok());
     }
 
     #[test]
     fn test_build_client_with_redirects() {
-        let args = RequestArgs {
-            url: "http://example.com".to_string(),
-            items: vec![],
-            auth: None,
-            verbose: false,
-            timeout: 60,
-            follow_redirects: true,
-            max_redirects: 5,
-            headers_only: false,
-            body_only: false,
-            download: false,
-            output: None,
-        };
+        let args = parse_args(&[
+            "httprs",
+            "http://example.com",
+            "--timeout",
+            "60",
+            "--follow",
+            "--max-redirects",
+            "5",
+        ]);
 
-        let client = build_client(&args);
+        let client = build_client(&args, None, tls::client_config(&args).unwrap());
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn test_build_client_with_cookie_jar() {
+        let args = parse_args(&["httprs", "http://example.com"]);
+        let client = build_client(&args, Some(Arc::new(CookieStoreMutex::default())), tls::client_config(&args).unwrap());
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn test_build_plain_client() {
+        // The target's --pin and client certificate are left out
+        let args = parse_args(&[
+            "httprs",
+            "https://api.example.com",
+            "--pin",
+            "sha256//not-a-pin",
+            "--cert",
+            "/nonexistent/client.pem",
+        ]);
+        assert!(tls::client_config(&args).is_err());
+        assert!(build_plain_client(&args).is_ok());
+    }
+
+    #[test]
+    fn test_build_client_with_cookie_items() {
+        let args = parse_args(&["httprs", "http://example.com", "theme~=dark"]);
+        assert!(build_client(&args, None, tls::client_config(&args).unwrap()).is_ok());
+    }
+
+    fn parse_args(argv: &[&str]) -> RequestArgs {
+        use clap::Parser;
+        RequestArgs::try_parse_from(argv).unwrap()
+    }
+
+    #[test]
+    fn test_default_headers_builtin() {
+        let headers = default_headers(&parse_args(&["httprs", "http://example.com"])).unwrap();
+        assert!(headers.get(header::USER_AGENT).unwrap().to_str().unwrap().starts_with("httprs/"));
+        assert_eq!(headers.get("X-Powered-By").unwrap(), "Rust");
+    }
+
+    #[test]
+    fn test_default_headers_unset_by_item() {
+        let args = parse_args(&["httprs", "http://example.com", "X-Powered-By:", "User-Agent:"]);
+        let headers = default_headers(&args).unwrap();
+        assert!(headers.get("X-Powered-By").is_none());
+        assert!(headers.get(header::USER_AGENT).is_none());
+    }
+
+    #[test]
+    fn test_default_headers_configured() {
+        let args = parse_args(&[
+            "httprs",
+            "http://example.com",
+            "--default-header",
+            "X-Powered-By:",
+            "--default-header",
+            "User-Agent:my-tool/1.0",
+            "--default-header",
+            "X-Team:platform",
+        ]);
+        let headers = default_headers(&args).unwrap();
+        assert!(headers.get("X-Powered-By").is_none());
+        assert_eq!(headers.get(header::USER_AGENT).unwrap(), "my-tool/1.0");
+        assert_eq!(headers.get("X-Team").unwrap(), "platform");
+    }
 }
@@ -0,0 +1,524 @@
+use anyhow::{anyhow, Result};
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::auth::{Auth, AuthType};
+use crate::cli::RequestArgs;
+use crate::hmac_auth::Encoding;
+use crate::oauth::Grant;
+use crate::paths::config_dir;
+use crate::tls::{TlsVersion, Verify};
+
+/// The httprs configuration file.
+///
+/// ```toml
+/// [defaults]
+/// timeout = 60
+/// follow = true
+/// headers = { "X-Team" = "platform" }
+///
+/// [hosts."api.example.com"]
+/// auth = "alice:secret"
+/// timeout = 10
+/// ```
+///
+/// Options in `[defaults]` apply to every request; `[hosts."<pattern>"]`
+/// sections apply to matching hosts (`example.com`, `*.example.com` or
+/// `example.com:8443`) and take precedence over the defaults. Command-line
+/// arguments always win. Credentials (`auth`, `auth-cmd`) are only allowed
+/// in host sections.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub defaults: Options,
+    #[serde(default)]
+    pub hosts: BTreeMap<String, Options>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Options {
+    pub timeout: Option<u64>,
+    pub follow: Option<bool>,
+    pub max_redirects: Option<usize>,
+    pub verbose: Option<bool>,
+    pub print: Option<PrintStyle>,
+    pub form: Option<bool>,
+    pub default_scheme: Option<String>,
+    pub auth: Option<String>,
+    /// Credential helper, like `--auth-cmd`
+    pub auth_cmd: Option<String>,
+    pub auth_type: Option<AuthType>,
+    pub aws_region: Option<String>,
+    pub aws_service: Option<String>,
+    pub oauth_token_url: Option<String>,
+    pub oauth_grant: Option<Grant>,
+    pub oauth_scope: Option<String>,
+    pub hmac_template: Option<String>,
+    pub hmac_headers: Option<Vec<String>>,
+    pub hmac_encoding: Option<Encoding>,
+    pub verify: Option<Verify>,
+    pub ssl: Option<TlsVersion>,
+    pub cert: Option<String>,
+    pub cert_key: Option<String>,
+    pub cert_p12: Option<String>,
+    /// Public key pins, like `--pin`
+    pub pins: Option<Vec<String>>,
+    /// `SCHEME:URL` proxies, like `--proxy`
+    pub proxies: Option<Vec<String>>,
+    pub proxy_auth: Option<String>,
+    pub no_proxy: Option<String>,
+    pub ignore_proxy_env: Option<bool>,
+    /// Extra default headers; an empty value drops a default header.
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Which parts of the response are printed, like `--headers` / `--body`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrintStyle {
+    All,
+    Headers,
+    Body,
+}
+
+/// The config file: `$HTTPRS_CONFIG`, or `config.toml` (or `config.json`)
+/// in the config directory.
+pub fn config_path() -> Result<Option<PathBuf>> {
+    if let Some(path) = std::env::var_os("HTTPRS_CONFIG") {
+        return Ok(Some(PathBuf::from(path)));
+    }
+    let dir = config_dir()?;
+    Ok(["config.toml", "config.json"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists()))
+}
+
+impl Config {
+    /// Loads the config file, or an empty config if there is none.
+    pub fn load() -> Result<Self> {
+        match config_path()? {
+            Some(path) => Self::from_file(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read config '{}': {}", path.display(), e))?;
+        let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        Self::parse(&text, is_json).map_err(|e| anyhow!("Invalid config '{}': {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str, is_json: bool) -> Result<Self> {
+        let config: Config = if is_json {
+            serde_json::from_str(text)?
+        } else {
+            toml::from_str(text)?
+        };
+        for options in std::iter::once(&config.defaults).chain(config.hosts.values()) {
+            if let Some(scheme) = &options.default_scheme
+                && scheme != "http"
+                && scheme != "https"
+            {
+                return Err(anyhow!("default-scheme must be 'http' or 'https', got '{}'", scheme));
+            }
+            if let Some(auth) = &options.auth {
+                auth.parse::<Auth>()?;
+            }
+        }
+        // Credentials in [defaults] would go to every host
+        if config.defaults.auth.is_some() || config.defaults.auth_cmd.is_some() {
+            return Err(anyhow!(
+                "auth and auth-cmd can't be set in [defaults], or they would be sent to every host: \
+                 move them to a [hosts.\"<host>\"] section"
+            ));
+        }
+        Ok(config)
+    }
+
+    /// The defaults with every host section matching `url` merged on top,
+    /// from the least to the most specific pattern.
+    pub fn options_for(&self, url: &str) -> Options {
+        let mut options = self.defaults.clone();
+        let Ok(url) = Url::parse(url) else {
+            return options;
+        };
+        let Some(host) = url.host_str() else {
+            return options;
+        };
+
+        let mut sections: Vec<(&String, &Options)> = self
+            .hosts
+            .iter()
+            .filter(|(pattern, _)| host_matches(pattern, host, url.port_or_known_default()))
+            .collect();
+        sections.sort_by_key(|(pattern, _)| specificity(pattern));
+        for (_, section) in sections {
+            options.merge(section);
+        }
+        options
+    }
+}
+
+fn split_port(pattern: &str) -> (&str, Option<u16>) {
+    match pattern.rsplit_once(':') {
+        Some((host, port)) => match port.parse() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => (pattern, None),
+        },
+        None => (pattern, None),
+    }
+}
+
+fn host_matches(pattern: &str, host: &str, port: Option<u16>) -> bool {
+    let (pattern, pattern_port) = split_port(pattern);
+    if pattern_port.is_some() && pattern_port != port {
+        return false;
+    }
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .to_ascii_lowercase()
+            .ends_with(&format!(".{}", suffix.to_ascii_lowercase())),
+        None => host.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Wildcards are less specific than exact hosts; patterns with a port are the
+/// most specific.
+fn specificity(pattern: &str) -> (bool, bool, usize) {
+    let (host, port) = split_port(pattern);
+    (port.is_some(), !host.starts_with("*."), host.len())
+}
+
+impl Options {
+    /// Overrides these options with the ones set in `other`.
+    pub fn merge(&mut self, other: &Options) {
+        macro_rules! merge_fields {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field.clone();
+                })*
+            };
+        }
+        merge_fields!(
+            timeout,
+            follow,
+            max_redirects,
+            verbose,
+            print,
+            form,
+            default_scheme,
+            auth,
+            auth_cmd,
+            auth_type,
+            aws_region,
+            aws_service,
+            oauth_token_url,
+            oauth_grant,
+            oauth_scope,
+            hmac_template,
+            hmac_headers,
+            hmac_encoding,
+            verify,
+            ssl,
+            cert,
+            cert_key,
+            cert_p12,
+            pins,
+            proxies,
+            proxy_auth,
+            no_proxy,
+            ignore_proxy_env
+        );
+        self.headers
+            .extend(other.headers.iter().map(|(name, value)| (name.clone(), value.clone())));
+    }
+
+    /// Fills in the options that weren't given on the command line.
+    /// `from_cli` tells whether an argument (by its field name) was given.
+    pub fn apply(&self, args: &mut RequestArgs, from_cli: impl Fn(&str) -> bool) -> Result<()> {
+        if let Some(timeout) = self.timeout
+            && !from_cli("timeout")
+        {
+            args.timeout = timeout;
+        }
+        if let Some(follow) = self.follow
+            && !from_cli("follow_redirects")
+        {
+            args.follow_redirects = follow;
+        }
+        if let Some(max_redirects) = self.max_redirects
+            && !from_cli("max_redirects")
+        {
+            args.max_redirects = max_redirects;
+        }
+        if let Some(verbose) = self.verbose
+            && !from_cli("verbose")
+        {
+            args.verbose = verbose;
+        }
+        if let Some(form) = self.form
+            && !from_cli("form")
+        {
+            args.form = form;
+        }
+        if let Some(print) = self.print
+            && !from_cli("headers_only")
+            && !from_cli("body_only")
+        {
+            args.headers_only = print == PrintStyle::Headers;
+            args.body_only = print == PrintStyle::Body;
+        }
+        if let Some(auth) = &self.auth
+            && !from_cli("auth")
+        {
+            args.auth = Some(auth.parse()?);
+        }
+        if let Some(auth_cmd) = &self.auth_cmd
+            && !from_cli("auth_cmd")
+        {
+            args.auth_cmd = Some(auth_cmd.clone());
+        }
+        if let Some(auth_type) = self.auth_type
+            && !from_cli("auth_type")
+        {
+            args.auth_type = Some(auth_type);
+        }
+        if let Some(aws_region) = &self.aws_region
+            && !from_cli("aws_region")
+        {
+            args.aws_region = Some(aws_region.clone());
+        }
+        if let Some(aws_service) = &self.aws_service
+            && !from_cli("aws_service")
+        {
+            args.aws_service = Some(aws_service.clone());
+        }
+        if let Some(oauth_token_url) = &self.oauth_token_url
+            && !from_cli("oauth_token_url")
+        {
+            args.oauth_token_url = Some(oauth_token_url.clone());
+        }
+        if let Some(oauth_grant) = self.oauth_grant
+            && !from_cli("oauth_grant")
+        {
+            args.oauth_grant = Some(oauth_grant);
+        }
+        if let Some(oauth_scope) = &self.oauth_scope
+            && !from_cli("oauth_scope")
+        {
+            args.oauth_scope = Some(oauth_scope.clone());
+        }
+        if let Some(hmac_template) = &self.hmac_template
+            && !from_cli("hmac_template")
+        {
+            args.hmac_template = Some(hmac_template.clone());
+        }
+        if let Some(headers) = &self.hmac_headers
+            && !from_cli("hmac_headers")
+        {
+            args.hmac_headers = headers.clone();
+        }
+        if let Some(hmac_encoding) = self.hmac_encoding
+            && !from_cli("hmac_encoding")
+        {
+            args.hmac_encoding = Some(hmac_encoding);
+        }
+        if let Some(verify) = &self.verify
+            && !from_cli("verify")
+        {
+            args.verify = Some(verify.clone());
+        }
+        if let Some(ssl) = self.ssl
+            && !from_cli("ssl")
+        {
+            args.ssl = Some(ssl);
+        }
+        // A client certificate from the command line replaces the config's
+        if !from_cli("cert") && !from_cli("cert_p12") {
+            args.cert = self.cert.clone();
+            args.cert_key = self.cert_key.clone();
+            args.cert_p12 = self.cert_p12.clone();
+        }
+        if let Some(pins) = &self.pins
+            && !from_cli("pins")
+        {
+            args.pins = pins.clone();
+        }
+        if let Some(proxies) = &self.proxies
+            && !from_cli("proxies")
+        {
+            args.proxies = proxies.clone();
+        }
+        if let Some(proxy_auth) = &self.proxy_auth
+            && !from_cli("proxy_auth")
+        {
+            args.proxy_auth = Some(proxy_auth.clone());
+        }
+        if let Some(no_proxy) = &self.no_proxy
+            && !from_cli("no_proxy")
+        {
+            args.no_proxy = Some(no_proxy.clone());
+        }
+        if let Some(ignore_proxy_env) = self.ignore_proxy_env
+            && !from_cli("ignore_proxy_env")
+        {
+            args.ignore_proxy_env = ignore_proxy_env;
+        }
+
+        // Config headers go first so `--default-header` overrides them
+        let mut headers: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        headers.append(&mut args.default_headers);
+        args.default_headers = headers;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    const CONFIG: &str = r#"
+[defaults]
+timeout = 60
+follow = true
+proxies = ["all:http://proxy.corp:3128"]
+proxy-auth = "jdoe"
+headers = { "X-Team" = "platform", "X-Powered-By" = "" }
+
+[hosts."*.example.com"]
+timeout = 20
+headers = { "X-Env" = "prod" }
+
+[hosts."api.example.com"]
+auth = "alice:secret"
+timeout = 10
+print = "body"
+pins = ["sha256//YhKJKSzoTt2b5FP18fvpHo7fJYqQCjAa3HWY3tvRMwE="]
+
+[hosts."api.example.com:8443"]
+max-redirects = 2
+auth-type = "digest"
+auth-cmd = "pass show camera"
+verify = false
+ssl = "tls1.3"
+"#;
+
+    fn parse_args(argv: &[&str]) -> RequestArgs {
+        RequestArgs::try_parse_from(argv).unwrap()
+    }
+
+    #[test]
+    fn host_patterns() {
+        assert!(host_matches("example.com", "EXAMPLE.com", Some(443)));
+        assert!(!host_matches("example.com", "api.example.com", Some(443)));
+        assert!(host_matches("*.example.com", "api.example.com", Some(443)));
+        assert!(!host_matches("*.example.com", "example.com", Some(443)));
+        assert!(host_matches("localhost:3000", "localhost", Some(3000)));
+        assert!(!host_matches("localhost:3000", "localhost", Some(80)));
+    }
+
+    #[test]
+    fn options_merged_by_specificity() {
+        let config = Config::parse(CONFIG, false).unwrap();
+
+        let options = config.options_for("https://api.example.com/users");
+        assert_eq!(options.timeout, Some(10));
+        assert_eq!(options.follow, Some(true));
+        assert_eq!(options.auth.as_deref(), Some("alice:secret"));
+        assert_eq!(options.print, Some(PrintStyle::Body));
+        assert_eq!(options.max_redirects, None);
+        assert_eq!(options.headers.len(), 3);
+        assert_eq!(options.headers["X-Env"], "prod");
+        assert_eq!(
+            options.pins,
+            Some(vec!["sha256//YhKJKSzoTt2b5FP18fvpHo7fJYqQCjAa3HWY3tvRMwE=".to_string()])
+        );
+
+        let options = config.options_for("https://api.example.com:8443/");
+        assert_eq!(options.max_redirects, Some(2));
+        assert_eq!(options.timeout, Some(10));
+        assert_eq!(options.auth_type, Some(AuthType::DIGEST));
+        assert_eq!(options.auth_cmd.as_deref(), Some("pass show camera"));
+        assert_eq!(options.verify, Some(Verify::No));
+        assert_eq!(options.ssl, Some(TlsVersion::Tls13));
+
+        let options = config.options_for("https://www.example.com/");
+        assert_eq!(options.timeout, Some(20));
+        assert_eq!(options.verify, None);
+        assert_eq!(options.auth, None);
+
+        let options = config.options_for("http://localhost:3000/");
+        assert_eq!(options, config.defaults);
+    }
+
+    #[test]
+    fn apply_keeps_command_line_values() {
+        let config = Config::parse(CONFIG, false).unwrap();
+        let options = config.options_for("https://api.example.com/");
+
+        let mut args = parse_args(&[
+            "httprs",
+            "https://api.example.com/",
+            "--timeout",
+            "5",
+            "--default-header",
+            "X-Team:search",
+        ]);
+        options.apply(&mut args, |id| id == "timeout").unwrap();
+
+        assert_eq!(args.timeout, 5);
+        assert!(args.follow_redirects);
+        assert!(args.body_only);
+        assert!(matches!(args.auth, Some(Auth::Basic { ref username, .. }) if username == "alice"));
+        // `--default-header` comes last, so it overrides the config
+        assert_eq!(
+            args.default_headers.last(),
+            Some(&("X-Team".to_string(), "search".to_string()))
+        );
+        assert!(args.default_headers.contains(&("X-Powered-By".to_string(), String::new())));
+        assert_eq!(args.proxies, vec!["all:http://proxy.corp:3128"]);
+        assert_eq!(args.proxy_auth.as_deref(), Some("jdoe"));
+
+        let mut args = parse_args(&["httprs", "https://api.example.com/", "-a", "bob:pw"]);
+        options.apply(&mut args, |id| id == "auth").unwrap();
+        assert!(matches!(args.auth, Some(Auth::Basic { ref username, .. }) if username == "bob"));
+    }
+
+    #[test]
+    fn json_config() {
+        let config = Config::parse(
+            r#"{"defaults": {"timeout": 5}, "hosts": {"localhost": {"default-scheme": "https"}}}"#,
+            true,
+        )
+        .unwrap();
+        assert_eq!(config.defaults.timeout, Some(5));
+        assert_eq!(config.hosts["localhost"].default_scheme.as_deref(), Some("https"));
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert!(Config::parse("[defaults]\ntimeout = \"soon\"", false).is_err());
+        assert!(Config::parse("[defaults]\ntimout = 5", false).is_err());
+        assert!(Config::parse("[defaults]\ndefault-scheme = \"ftp\"", false).is_err());
+        assert!(Config::parse("[defaults]\nauth-type = \"ntlm\"", false).is_err());
+
+        let err = Config::parse("[defaults]\nauth = \"alice:secret\"", false).unwrap_err();
+        assert!(err.to_string().contains("can't be set in [defaults]"));
+        assert!(Config::parse("[defaults]\nauth-cmd = \"pass show api\"", false).is_err());
+    }
+}
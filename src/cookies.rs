@@ -0,0 +1,371 @@
+use anyhow::{anyhow, Result};
+use cookie_store::{CookieDomain, CookieExpiration};
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use reqwest_cookie_store::{CookieStore, CookieStoreMutex, RawCookie};
+use std::path::Path;
+use std::sync::Arc;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
+
+use crate::cli::{RequestArgs, RequestItem};
+use crate::paths::write_private;
+use crate::session::Session;
+
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// Opens the cookie store shared by `--session` and `--cookie-jar`, if
+/// either is used. Cookies from both are merged into one store.
+pub fn open_jar(args: &RequestArgs, session: Option<&Session>) -> Result<Option<Arc<CookieStoreMutex>>> {
+    let jar = match (session, &args.cookie_jar) {
+        (None, None) => return Ok(None),
+        (Some(session), _) => session.cookie_jar(),
+        (None, Some(_)) => Arc::new(CookieStoreMutex::default()),
+    };
+
+    if let Some(path) = &args.cookie_jar {
+        let path = Path::new(path);
+        // Like curl, a missing jar is created when the cookies are saved
+        if path.exists() {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read cookie jar '{}': {}", path.display(), e))?;
+            let mut store = jar.lock().map_err(|_| anyhow!("Cookie jar lock poisoned"))?;
+            load_netscape(&text, &mut store)
+                .map_err(|e| anyhow!("Invalid cookie jar '{}': {}", path.display(), e))?;
+        }
+    }
+    Ok(Some(jar))
+}
+
+/// Writes the cookies in `jar` back to the `--cookie-jar` file, if given.
+pub fn save_jar(args: &RequestArgs, jar: &CookieStoreMutex) -> Result<()> {
+    let Some(path) = &args.cookie_jar else {
+        return Ok(());
+    };
+    let store = jar.lock().map_err(|_| anyhow!("Cookie jar lock poisoned"))?;
+    write_private(Path::new(path), to_netscape(&store).as_bytes())
+}
+
+/// Adds the cookies of a Netscape-format cookie file (as written by curl and
+/// browser export extensions) to `store`. Expired cookies are skipped.
+///
+/// Each line holds seven tab-separated fields: domain, include subdomains,
+/// path, secure, expiry (Unix time, `0` for a session cookie), name and value.
+/// Lines starting with `#` are comments, except for curl's `#HttpOnly_` prefix.
+pub fn load_netscape(text: &str, store: &mut CookieStore) -> Result<()> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+            Some(rest) => (rest, true),
+            None => (line, false),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        // Some exporters drop the trailing tab of an empty value
+        let [domain, subdomains, path, secure, expires, name, value] = match fields[..] {
+            [domain, subdomains, path, secure, expires, name, value] => {
+                [domain, subdomains, path, secure, expires, name, value]
+            }
+            [domain, subdomains, path, secure, expires, name] => {
+                [domain, subdomains, path, secure, expires, name, ""]
+            }
+            _ => {
+                return Err(anyhow!(
+                    "line {}: expected 7 tab-separated fields, found {}",
+                    number + 1,
+                    fields.len()
+                ));
+            }
+        };
+
+        let expires: i64 = expires
+            .parse()
+            .map_err(|_| anyhow!("line {}: invalid expiry '{}'", number + 1, expires))?;
+        if expires != 0 && expires <= now {
+            continue;
+        }
+
+        let host = domain.trim_start_matches('.');
+        let path = if path.is_empty() { "/" } else { path };
+        let mut cookie = RawCookie::build((name.to_string(), value.to_string()))
+            .path(path.to_string())
+            .secure(parse_flag(secure))
+            .http_only(http_only)
+            .build();
+        if parse_flag(subdomains) {
+            cookie.set_domain(host.to_string());
+        }
+        if expires != 0 {
+            let expires = OffsetDateTime::from_unix_timestamp(expires)
+                .map_err(|_| anyhow!("line {}: invalid expiry '{}'", number + 1, expires))?;
+            cookie.set_expires(expires);
+        }
+
+        let url = Url::parse(&format!("https://{}{}", host, path))
+            .map_err(|e| anyhow!("line {}: invalid domain '{}': {}", number + 1, domain, e))?;
+        store
+            .insert_raw(&cookie, &url)
+            .map_err(|e| anyhow!("line {}: {}", number + 1, e))?;
+    }
+    Ok(())
+}
+
+fn parse_flag(s: &str) -> bool {
+    s.eq_ignore_ascii_case("TRUE")
+}
+
+fn flag(b: bool) -> &'static str {
+    if b { "TRUE" } else { "FALSE" }
+}
+
+/// Formats the unexpired cookies in `store` as a Netscape cookie file.
+pub fn to_netscape(store: &CookieStore) -> String {
+    let mut out = String::from("# Netscape HTTP Cookie File\n# Generated by httprs. Edit at your own risk.\n\n");
+
+    for cookie in store.iter_unexpired() {
+        let domain = match &cookie.domain {
+            CookieDomain::HostOnly(host) => host.clone(),
+            CookieDomain::Suffix(suffix) => format!(".{}", suffix),
+            CookieDomain::NotPresent | CookieDomain::Empty => continue,
+        };
+        let expires = match cookie.expires {
+            CookieExpiration::AtUtc(at) => at.unix_timestamp(),
+            CookieExpiration::SessionEnd => 0,
+        };
+        if cookie.http_only().unwrap_or(false) {
+            out.push_str(HTTP_ONLY_PREFIX);
+        }
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+            domain,
+            flag(matches!(cookie.domain, CookieDomain::Suffix(_))),
+            String::from(&cookie.path),
+            flag(cookie.secure().unwrap_or(false)),
+            expires,
+            cookie.name(),
+            cookie.value()
+        ));
+    }
+    out
+}
+
+/// Cookie provider that adds `name~=value` request item cookies to requests
+/// for the request's host, on top of the cookies in the jar.
+///
+/// The ad-hoc cookies are never stored in the jar, so they aren't saved to a
+/// session or `--cookie-jar` file.
+pub struct RequestCookies {
+    jar: Arc<CookieStoreMutex>,
+    host: Option<String>,
+    cookies: Vec<(String, String)>,
+}
+
+impl RequestCookies {
+    /// Wraps `jar` with the cookie items of `args`, or returns `None` if there
+    /// aren't any.
+    pub fn from_args(args: &RequestArgs, jar: Arc<CookieStoreMutex>) -> Option<Self> {
+        let cookies: Vec<(String, String)> = args
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                RequestItem::Cookie(name, value) => Some((name.clone(), value.clone())),
+                _ => None,
+            })
+            .collect();
+        if cookies.is_empty() {
+            return None;
+        }
+
+        let host = Url::parse(&args.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string));
+        Some(Self { jar, host, cookies })
+    }
+}
+
+impl reqwest::cookie::CookieStore for RequestCookies {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        self.jar.set_cookies(cookie_headers, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        if url.host_str() != self.host.as_deref() {
+            return self.jar.cookies(url);
+        }
+
+        // Ad-hoc cookies replace jar cookies of the same name
+        let mut pairs: Vec<String> = match self.jar.lock() {
+            Ok(store) => store
+                .get_request_values(url)
+                .filter(|(name, _)| !self.cookies.iter().any(|(adhoc, _)| adhoc == name))
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        pairs.extend(self.cookies.iter().map(|(name, value)| format!("{}={}", name, value)));
+        HeaderValue::from_str(&pairs.join("; ")).ok()
+    }
+}
+
+/// One-line summary of a `Set-Cookie` header: the cookie and its attributes.
+pub fn describe_set_cookie(header: &str) -> Option<String> {
+    let cookie = RawCookie::parse(header).ok()?;
+
+    let mut attributes = Vec::new();
+    if let Some(domain) = cookie.domain() {
+        attributes.push(format!("domain={}", domain));
+    }
+    if let Some(path) = cookie.path() {
+        attributes.push(format!("path={}", path));
+    }
+    if let Some(max_age) = cookie.max_age() {
+        attributes.push(format!("max-age={}s", max_age.whole_seconds()));
+    } else if let Some(expires) = cookie.expires_datetime() {
+        let expires = expires
+            .format(&Rfc2822)
+            .unwrap_or_else(|_| expires.unix_timestamp().to_string());
+        attributes.push(format!("expires={}", expires));
+    } else {
+        attributes.push("session".to_string());
+    }
+    if cookie.secure().unwrap_or(false) {
+        attributes.push("secure".to_string());
+    }
+    if cookie.http_only().unwrap_or(false) {
+        attributes.push("httponly".to_string());
+    }
+    if let Some(same_site) = cookie.same_site() {
+        attributes.push(format!("samesite={}", same_site));
+    }
+
+    Some(format!("{}={} ({})", cookie.name(), cookie.value(), attributes.join(", ")))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+
+    /// Cookies sent to `url`, sorted by name since the store is unordered.
+    fn request_values(store: &CookieStore, url: &str) -> Vec<(String, String)> {
+        let mut values: Vec<(String, String)> = store
+            .get_request_values(&Url::parse(url).unwrap())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        values.sort();
+        values
+    }
+
+    fn pair(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn load_netscape_honours_attributes() {
+        let text = "# Netscape HTTP Cookie File\n\
+            \n\
+            .example.com\tTRUE\t/\tFALSE\t0\tshared\t1\n\
+            api.example.com\tFALSE\t/v1\tFALSE\t4102444800\tscoped\t2\n\
+            example.com\tFALSE\t/\tTRUE\t0\tsecure\t3\n\
+            #HttpOnly_example.com\tFALSE\t/\tFALSE\t0\thidden\t4\n\
+            example.com\tFALSE\t/\tFALSE\t1\texpired\t5\n";
+        let mut store = CookieStore::default();
+        load_netscape(text, &mut store).unwrap();
+
+        assert_eq!(
+            request_values(&store, "https://example.com/"),
+            vec![pair("hidden", "4"), pair("secure", "3"), pair("shared", "1")]
+        );
+        // Secure cookies aren't sent over plain HTTP
+        assert_eq!(
+            request_values(&store, "http://example.com/"),
+            vec![pair("hidden", "4"), pair("shared", "1")]
+        );
+        // Subdomains only get domain cookies; paths must match
+        assert_eq!(request_values(&store, "https://api.example.com/"), vec![pair("shared", "1")]);
+        assert_eq!(
+            request_values(&store, "https://api.example.com/v1/users"),
+            vec![pair("scoped", "2"), pair("shared", "1")]
+        );
+    }
+
+    #[test]
+    fn load_netscape_rejects_malformed_lines() {
+        let mut store = CookieStore::default();
+        let err = load_netscape("example.com\tFALSE\t/\n", &mut store).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+
+        let err = load_netscape("example.com\tFALSE\t/\tFALSE\tsoon\ta\tb\n", &mut store).unwrap_err();
+        assert!(err.to_string().contains("invalid expiry"));
+
+        // An empty value may lose its trailing tab
+        load_netscape("example.com\tFALSE\t/\tFALSE\t0\tempty\n", &mut store).unwrap();
+        assert_eq!(request_values(&store, "https://example.com/"), vec![pair("empty", "")]);
+    }
+
+    #[test]
+    fn netscape_round_trip() {
+        let text = ".example.com\tTRUE\t/\tFALSE\t4102444800\tshared\t1\n\
+            #HttpOnly_example.com\tFALSE\t/app\tTRUE\t0\tsid\tabc\n";
+        let mut store = CookieStore::default();
+        load_netscape(text, &mut store).unwrap();
+
+        let written = to_netscape(&store);
+        assert!(written.starts_with("# Netscape HTTP Cookie File\n"));
+        assert!(written.contains(".example.com\tTRUE\t/\tFALSE\t4102444800\tshared\t1\n"));
+        assert!(written.contains("#HttpOnly_example.com\tFALSE\t/app\tTRUE\t0\tsid\tabc\n"));
+
+        let mut reloaded = CookieStore::default();
+        load_netscape(&written, &mut reloaded).unwrap();
+        assert_eq!(
+            request_values(&reloaded, "https://example.com/app"),
+            request_values(&store, "https://example.com/app")
+        );
+    }
+
+    #[test]
+    fn request_cookies_added_for_request_host() {
+        use clap::Parser;
+        let args = RequestArgs::try_parse_from(["httprs", "https://example.com/", "theme~=dark", "sid~=override"])
+            .unwrap();
+
+        let jar = Arc::new(CookieStoreMutex::default());
+        jar.lock()
+            .unwrap()
+            .parse("sid=abc; Path=/", &Url::parse("https://example.com/").unwrap())
+            .unwrap();
+        let provider = RequestCookies::from_args(&args, jar.clone()).unwrap();
+
+        let header = provider.cookies(&Url::parse("https://example.com/x").unwrap()).unwrap();
+        assert_eq!(header, "theme=dark; sid=override");
+        assert!(provider.cookies(&Url::parse("https://other.com/").unwrap()).is_none());
+
+        // The jar itself is left untouched
+        assert_eq!(
+            request_values(&jar.lock().unwrap(), "https://example.com/"),
+            vec![pair("sid", "abc")]
+        );
+    }
+
+    #[test]
+    fn describe_set_cookie_lists_attributes() {
+        assert_eq!(
+            describe_set_cookie("sid=abc; Path=/; Secure; HttpOnly").unwrap(),
+            "sid=abc (path=/, session, secure, httponly)"
+        );
+        assert_eq!(
+            describe_set_cookie("id=1; Domain=example.com; Max-Age=3600; SameSite=Lax").unwrap(),
+            "id=1 (domain=example.com, max-age=3600s, samesite=Lax)"
+        );
+        assert!(describe_set_cookie("not a cookie").is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Result};
+use std::io::IsTerminal;
+use std::process::{Command, Stdio};
+
+use crate::auth::Auth;
+
+/// Runs a credential helper (`--auth-cmd`) through the shell and returns the
+/// first line it prints.
+///
+/// The helper's stdin and stderr are left attached to the terminal, so tools
+/// like `pass` or `op` can still ask for a passphrase.
+pub fn run_helper(command: &str) -> Result<String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let output = shell
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| anyhow!("Failed to run auth command `{}`: {}", command, e))?;
+    if !output.status.success() {
+        return Err(anyhow!("Auth command `{}` failed ({})", command, output.status));
+    }
+
+    let stdout = String::from_utf8(output.stdout)
+        .map_err(|_| anyhow!("Auth command `{}` printed invalid UTF-8", command))?;
+    let secret = stdout.lines().next().unwrap_or_default().trim_end_matches('\r');
+    if secret.is_empty() {
+        return Err(anyhow!("Auth command `{}` printed nothing", command));
+    }
+    Ok(secret.to_string())
+}
+
+/// Completes the credentials with the output of the auth command.
+///
+/// Without `--auth`, the output is parsed like an `--auth` value
+/// (`username:password` or a token); otherwise it is the missing secret:
+/// the password, the OAuth2 client secret or `ACCESS_KEY:SECRET_KEY` for AWS.
+/// The command only runs when something is missing.
+pub fn fill_from_helper(auth: Option<Auth>, command: &str) -> Result<Option<Auth>> {
+    let auth = match auth {
+        None => run_helper(command)?.parse()?,
+        Some(Auth::Basic { username, password: None }) => Auth::Basic {
+            username,
+            password: Some(run_helper(command)?),
+        },
+        Some(Auth::Digest { username, password: None }) => Auth::Digest {
+            username,
+            password: Some(run_helper(command)?),
+        },
+        Some(Auth::OAuth2 { client_id, client_secret: None }) => Auth::OAuth2 {
+            client_id,
+            client_secret: Some(run_helper(command)?),
+        },
+        Some(Auth::AwsSigV4 { access_key: None, secret_key: None }) => {
+            let keys = run_helper(command)?;
+            let (access_key, secret_key) = keys
+                .split_once(':')
+                .ok_or_else(|| anyhow!("The auth command must print ACCESS_KEY:SECRET_KEY for AWS SigV4"))?;
+            Auth::AwsSigV4 {
+                access_key: Some(access_key.to_string()),
+                secret_key: Some(secret_key.to_string()),
+            }
+        }
+        Some(auth) => auth,
+    };
+    Ok(Some(auth))
+}
+
+/// Reads a password from the terminal without echoing it. Returns `None`
+/// when there is no terminal to ask on.
+pub fn prompt_password(prompt: &str) -> Option<String> {
+    if !has_terminal() {
+        return None;
+    }
+    rpassword::prompt_password(prompt).ok()
+}
+
+/// Whether there is a terminal to prompt on; rpassword reads from the
+/// controlling terminal rather than stdin, so a piped body doesn't matter.
+fn has_terminal() -> bool {
+    if cfg!(unix) {
+        std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty").is_ok()
+    } else {
+        std::io::stdin().is_terminal()
+    }
+}
+
+/// Asks for the password of Basic or Digest credentials given without one;
+/// without a terminal (or when not `interactive`) that is an error rather
+/// than a request sent with no password.
+pub fn prompt_missing_password(auth: Option<Auth>, host: &str, interactive: bool) -> Result<Option<Auth>> {
+    let ask = |username: &str| {
+        interactive
+            .then(|| prompt_password(&format!("Password for {}@{}: ", username, host)))
+            .flatten()
+            .ok_or_else(|| {
+                anyhow!(
+                    "No password for {}@{}: pass --auth USER:PASSWORD or --auth-cmd, or run in a terminal",
+                    username,
+                    host
+                )
+            })
+    };
+    Ok(match auth {
+        Some(Auth::Basic { username, password: None }) => {
+            let password = Some(ask(&username)?);
+            Some(Auth::Basic { username, password })
+        }
+        Some(Auth::Digest { username, password: None }) => {
+            let password = Some(ask(&username)?);
+            Some(Auth::Digest { username, password })
+        }
+        auth => auth,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn helper_output_fills_missing_secret() {
+        let basic = Auth::Basic {
+            username: "alice".into(),
+            password: None,
+        };
+        assert_eq!(
+            fill_from_helper(Some(basic), "printf 's3 cret\\nignored\\n'").unwrap(),
+            Some(Auth::Basic {
+                username: "alice".into(),
+                password: Some("s3 cret".into())
+            })
+        );
+
+        let aws = Auth::AwsSigV4 {
+            access_key: None,
+            secret_key: None,
+        };
+        assert_eq!(
+            fill_from_helper(Some(aws), "echo AKID:secret").unwrap(),
+            Some(Auth::AwsSigV4 {
+                access_key: Some("AKID".into()),
+                secret_key: Some("secret".into())
+            })
+        );
+    }
+
+    #[test]
+    fn helper_output_parsed_as_auth() {
+        assert_eq!(
+            fill_from_helper(None, "echo bob:pw").unwrap(),
+            Some(Auth::Basic {
+                username: "bob".into(),
+                password: Some("pw".into())
+            })
+        );
+        assert_eq!(
+            fill_from_helper(None, "echo bearer:tok").unwrap(),
+            Some(Auth::Bearer("tok".into()))
+        );
+    }
+
+    #[test]
+    fn helper_not_run_when_complete() {
+        let bearer = Some(Auth::Bearer("tok".into()));
+        assert_eq!(fill_from_helper(bearer.clone(), "exit 1").unwrap(), bearer);
+    }
+
+    #[test]
+    fn missing_password_without_prompt() {
+        let basic = Auth::Basic {
+            username: "alice".into(),
+            password: None,
+        };
+        let err = prompt_missing_password(Some(basic), "example.com", false).unwrap_err();
+        assert!(err.to_string().contains("No password for alice@example.com"));
+
+        let complete = Some(Auth::Basic {
+            username: "alice".into(),
+            password: Some("pw".into()),
+        });
+        assert_eq!(prompt_missing_password(complete.clone(), "example.com", false).unwrap(), complete);
+    }
+
+    #[test]
+    fn helper_failures() {
+        assert!(run_helper("exit 3").is_err());
+        assert!(run_helper("true").is_err());
+        assert_eq!(run_helper("echo ok").unwrap(), "ok");
+    }
+}
@@ -0,0 +1,409 @@
+use anyhow::{anyhow, Result};
+use md5::Md5;
+use reqwest::{header, Client, Request, Response, StatusCode};
+use sha2::{Digest as _, Sha256};
+
+use crate::auth::{Auth, AuthScheme};
+
+/// Digest hash algorithms from RFC 7616.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl Algorithm {
+    fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "MD5" => Some(Algorithm::Md5),
+            "MD5-SESS" => Some(Algorithm::Md5Sess),
+            "SHA-256" => Some(Algorithm::Sha256),
+            "SHA-256-SESS" => Some(Algorithm::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Md5Sess => "MD5-sess",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn is_sess(self) -> bool {
+        matches!(self, Algorithm::Md5Sess | Algorithm::Sha256Sess)
+    }
+
+    fn hash(self, data: &[u8]) -> String {
+        match self {
+            Algorithm::Md5 | Algorithm::Md5Sess => format!("{:x}", Md5::digest(data)),
+            Algorithm::Sha256 | Algorithm::Sha256Sess => format!("{:x}", Sha256::digest(data)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Qop {
+    Auth,
+    AuthInt,
+}
+
+/// A `WWW-Authenticate: Digest ...` challenge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Challenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    pub algorithm: Algorithm,
+    /// `None` for RFC 2069 servers that don't send `qop`
+    pub qop: Option<Qop>,
+}
+
+/// Splits `key=value, key="quoted, value"` auth parameters.
+fn parse_params(s: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut chars = s.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && *c != ',') {
+            key.push(c);
+        }
+        if key.is_empty() {
+            break;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            if chars.next_if_eq(&'"').is_some() {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => value.extend(chars.next()),
+                        c => value.push(c),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| *c != ',') {
+                    value.push(c);
+                }
+            }
+        }
+        params.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+    params
+}
+
+impl Challenge {
+    /// Parses a `WWW-Authenticate` value; returns `None` for other schemes or
+    /// unsupported algorithms and qop values.
+    pub fn parse(header: &str) -> Option<Self> {
+        let (scheme, rest) = header.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let mut realm = None;
+        let mut nonce = None;
+        let mut opaque = None;
+        let mut algorithm = Algorithm::Md5;
+        let mut qop = None;
+        for (key, value) in parse_params(rest) {
+            match key.as_str() {
+                "realm" => realm = Some(value),
+                "nonce" => nonce = Some(value),
+                "opaque" => opaque = Some(value),
+                "algorithm" => algorithm = Algorithm::parse(&value)?,
+                "qop" => {
+                    let offered: Vec<&str> = value.split(',').map(str::trim).collect();
+                    // Prefer `auth`; `auth-int` also covers the body
+                    qop = Some(if offered.iter().any(|q| q.eq_ignore_ascii_case("auth")) {
+                        Qop::Auth
+                    } else if offered.iter().any(|q| q.eq_ignore_ascii_case("auth-int")) {
+                        Qop::AuthInt
+                    } else {
+                        return None;
+                    });
+                }
+                _ => {}
+            }
+        }
+        Some(Challenge {
+            realm: realm?,
+            nonce: nonce?,
+            opaque,
+            algorithm,
+            qop,
+        })
+    }
+
+    /// The strongest Digest challenge among `WWW-Authenticate` headers.
+    pub fn from_headers(headers: &header::HeaderMap) -> Option<Self> {
+        headers
+            .get_all(header::WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(Challenge::parse)
+            .max_by_key(|challenge| {
+                matches!(challenge.algorithm, Algorithm::Sha256 | Algorithm::Sha256Sess)
+            })
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `--auth-type digest`
+pub struct DigestScheme;
+
+impl AuthScheme for DigestScheme {
+    fn name(&self) -> &'static str {
+        "digest"
+    }
+
+    fn credentials(&self, auth: Auth) -> Result<Auth> {
+        match auth {
+            Auth::Basic { username, password } | Auth::Digest { username, password } => {
+                Ok(Auth::Digest { username, password })
+            }
+            _ => Err(anyhow!("Digest auth needs credentials as username:password")),
+        }
+    }
+
+    fn needs_credentials(&self) -> bool {
+        true
+    }
+}
+
+/// Answers a Digest challenge, counting how often its nonce has been used.
+///
+/// The count is per `DigestAuth`, and [`send`] makes one for each request,
+/// so the requests httprs sends always carry `nc=00000001`.
+pub struct DigestAuth {
+    username: String,
+    password: String,
+    challenge: Challenge,
+    nonce_count: u32,
+}
+
+impl DigestAuth {
+    pub fn new(username: &str, password: &str, challenge: Challenge) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+            challenge,
+            nonce_count: 0,
+        }
+    }
+
+    /// The `Authorization` header for a request; `uri` is the request target
+    /// (path and query) and `body` is only used for `qop=auth-int`.
+    pub fn authorization(&mut self, method: &str, uri: &str, body: &[u8], cnonce: &str) -> String {
+        self.nonce_count += 1;
+        let challenge = &self.challenge;
+        let algorithm = challenge.algorithm;
+        let nc = format!("{:08x}", self.nonce_count);
+
+        let mut ha1 = algorithm.hash(
+            format!("{}:{}:{}", self.username, challenge.realm, self.password).as_bytes(),
+        );
+        if algorithm.is_sess() {
+            ha1 = algorithm.hash(format!("{}:{}:{}", ha1, challenge.nonce, cnonce).as_bytes());
+        }
+        let ha2 = match challenge.qop {
+            Some(Qop::AuthInt) => {
+                algorithm.hash(format!("{}:{}:{}", method, uri, algorithm.hash(body)).as_bytes())
+            }
+            _ => algorithm.hash(format!("{}:{}", method, uri).as_bytes()),
+        };
+        let response = match challenge.qop {
+            Some(qop) => {
+                let qop = if qop == Qop::Auth { "auth" } else { "auth-int" };
+                algorithm.hash(
+                    format!("{}:{}:{}:{}:{}:{}", ha1, challenge.nonce, nc, cnonce, qop, ha2).as_bytes(),
+                )
+            }
+            None => algorithm.hash(format!("{}:{}:{}", ha1, challenge.nonce, ha2).as_bytes()),
+        };
+
+        let mut params = vec![
+            format!("username={}", quote(&self.username)),
+            format!("realm={}", quote(&challenge.realm)),
+            format!("nonce={}", quote(&challenge.nonce)),
+            format!("uri={}", quote(uri)),
+            format!("algorithm={}", algorithm.name()),
+            format!("response={}", quote(&response)),
+        ];
+        if let Some(qop) = challenge.qop {
+            params.push(format!("qop={}", if qop == Qop::Auth { "auth" } else { "auth-int" }));
+            params.push(format!("nc={}", nc));
+            params.push(format!("cnonce={}", quote(cnonce)));
+        }
+        if let Some(opaque) = &challenge.opaque {
+            params.push(format!("opaque={}", quote(opaque)));
+        }
+        format!("Digest {}", params.join(", "))
+    }
+}
+
+fn new_cnonce() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("Failed to generate a cnonce: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Sends `request`; if the server answers 401 with a Digest challenge, sends
+/// it again with the computed `Authorization` header.
+///
+/// A streamed body (multipart upload) can't be sent twice, so it only fails
+/// if a challenge actually comes back.
+pub async fn send(client: &Client, request: Request, username: &str, password: &str) -> Result<Response> {
+    let retry = request.try_clone();
+
+    let resp = client.execute(request).await?;
+    if resp.status() != StatusCode::UNAUTHORIZED {
+        return Ok(resp);
+    }
+    let Some(challenge) = Challenge::from_headers(resp.headers()) else {
+        return Ok(resp);
+    };
+    let Some(mut retry) = retry else {
+        return Err(anyhow!(
+            "The server asked for Digest auth, but a streamed body (multipart upload) can't be sent again to answer it"
+        ));
+    };
+
+    let url = retry.url();
+    let uri = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let body = retry.body().and_then(|body| body.as_bytes()).unwrap_or_default();
+    let authorization = DigestAuth::new(username, password, challenge).authorization(
+        retry.method().as_str(),
+        &uri,
+        body,
+        &new_cnonce()?,
+    );
+    retry
+        .headers_mut()
+        .insert(header::AUTHORIZATION, header::HeaderValue::from_str(&authorization)?);
+    Ok(client.execute(retry).await?)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example from RFC 7616, section 3.9.1
+    const NONCE: &str = "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v";
+    const CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+    const OPAQUE: &str = "FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS";
+
+    fn challenge(algorithm: &str) -> Challenge {
+        Challenge::parse(&format!(
+            "Digest realm=\"http-auth@example.org\", qop=\"auth, auth-int\", algorithm={}, nonce=\"{}\", opaque=\"{}\"",
+            algorithm, NONCE, OPAQUE
+        ))
+        .unwrap()
+    }
+
+    fn response_param(header: &str) -> String {
+        parse_params(header.strip_prefix("Digest ").unwrap())
+            .into_iter()
+            .find(|(key, _)| key == "response")
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn parse_challenge() {
+        let parsed = challenge("SHA-256");
+        assert_eq!(parsed.realm, "http-auth@example.org");
+        assert_eq!(parsed.nonce, NONCE);
+        assert_eq!(parsed.opaque.as_deref(), Some(OPAQUE));
+        assert_eq!(parsed.algorithm, Algorithm::Sha256);
+        assert_eq!(parsed.qop, Some(Qop::Auth));
+
+        let parsed = Challenge::parse("digest realm=\"a, b\", nonce=abc, qop=\"auth-int\"").unwrap();
+        assert_eq!(parsed.realm, "a, b");
+        assert_eq!(parsed.nonce, "abc");
+        assert_eq!(parsed.algorithm, Algorithm::Md5);
+        assert_eq!(parsed.qop, Some(Qop::AuthInt));
+
+        assert!(Challenge::parse("Basic realm=\"x\"").is_none());
+        assert!(Challenge::parse("Digest realm=\"x\", nonce=\"n\", algorithm=SHA-512-256").is_none());
+        assert!(Challenge::parse("Digest realm=\"x\"").is_none());
+    }
+
+    #[test]
+    fn prefers_sha256_challenge() {
+        let mut headers = header::HeaderMap::new();
+        for algorithm in ["MD5", "SHA-256"] {
+            headers.append(
+                header::WWW_AUTHENTICATE,
+                format!("Digest realm=\"r\", nonce=\"n\", algorithm={}", algorithm).parse().unwrap(),
+            );
+        }
+        headers.append(header::WWW_AUTHENTICATE, "Basic realm=\"r\"".parse().unwrap());
+        assert_eq!(Challenge::from_headers(&headers).unwrap().algorithm, Algorithm::Sha256);
+    }
+
+    #[test]
+    fn rfc7616_md5_response() {
+        let mut digest = DigestAuth::new("Mufasa", "Circle of Life", challenge("MD5"));
+        let header = digest.authorization("GET", "/dir/index.html", b"", CNONCE);
+        assert_eq!(response_param(&header), "8ca523f5e9506fed4657c9700eebdbec");
+        assert!(header.contains("uri=\"/dir/index.html\""));
+        assert!(header.contains("qop=auth, nc=00000001"));
+        assert!(header.contains(&format!("opaque=\"{}\"", OPAQUE)));
+    }
+
+    #[test]
+    fn rfc7616_sha256_response() {
+        let mut digest = DigestAuth::new("Mufasa", "Circle of Life", challenge("SHA-256"));
+        let header = digest.authorization("GET", "/dir/index.html", b"", CNONCE);
+        assert_eq!(
+            response_param(&header),
+            "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1"
+        );
+        assert!(header.contains("algorithm=SHA-256"));
+    }
+
+    #[test]
+    fn nonce_count_increments() {
+        let mut digest = DigestAuth::new("Mufasa", "Circle of Life", challenge("MD5"));
+        let first = digest.authorization("GET", "/", b"", CNONCE);
+        let second = digest.authorization("GET", "/", b"", CNONCE);
+        assert!(first.contains("nc=00000001"));
+        assert!(second.contains("nc=00000002"));
+        assert_ne!(response_param(&first), response_param(&second));
+    }
+
+    #[test]
+    fn auth_int_hashes_body() {
+        let challenge = Challenge::parse("Digest realm=\"r\", nonce=\"n\", qop=\"auth-int\"").unwrap();
+        let mut digest = DigestAuth::new("u", "p", challenge.clone());
+        let with_body = digest.authorization("POST", "/", b"{\"a\":1}", "c");
+        let mut digest = DigestAuth::new("u", "p", challenge);
+        let empty = digest.authorization("POST", "/", b"", "c");
+        assert!(with_body.contains("qop=auth-int"));
+        assert_ne!(response_param(&with_body), response_param(&empty));
+    }
+
+    #[test]
+    fn rfc2069_without_qop() {
+        let challenge = Challenge::parse("Digest realm=\"r\", nonce=\"n\"").unwrap();
+        let header = DigestAuth::new("u", "p", challenge).authorization("GET", "/", b"", "c");
+        assert!(!header.contains("qop="));
+        assert!(!header.contains("nc="));
+    }
+}
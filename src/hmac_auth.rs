@@ -0,0 +1,333 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use clap::ValueEnum;
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::Request;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::auth::{Auth, AuthScheme};
+use crate::cli::RequestArgs;
+
+/// The string to sign unless `--hmac-template` says otherwise.
+pub const DEFAULT_TEMPLATE: &str = "{method}\\n{path}\\n{timestamp}\\n{body}";
+
+/// Headers added unless `--hmac-header` says otherwise.
+pub const DEFAULT_HEADERS: &[&str] = &["X-Timestamp:{timestamp}", "X-Signature:{signature}"];
+
+/// How the signature is written in the headers.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+/// `--auth-type hmac`, with the shared secret as `--auth [KEY_ID:]SECRET`.
+pub struct HmacScheme;
+
+impl AuthScheme for HmacScheme {
+    fn name(&self) -> &'static str {
+        "hmac"
+    }
+
+    fn credentials(&self, auth: Auth) -> Result<Auth> {
+        match auth {
+            Auth::Basic {
+                username,
+                password: Some(secret),
+            } => Ok(Auth::Hmac {
+                key_id: Some(username),
+                secret,
+            }),
+            Auth::Basic {
+                username: secret,
+                password: None,
+            }
+            | Auth::Bearer(secret) => Ok(Auth::Hmac { key_id: None, secret }),
+            auth @ Auth::Hmac { .. } => Ok(auth),
+            _ => Err(anyhow!("HMAC signing needs the shared secret as [KEY_ID:]SECRET")),
+        }
+    }
+
+    fn needs_credentials(&self) -> bool {
+        true
+    }
+}
+
+/// What to sign and where the signature goes, from the `--hmac-*` options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signer {
+    pub template: String,
+    pub headers: Vec<(HeaderName, String)>,
+    pub encoding: Encoding,
+}
+
+impl Signer {
+    pub fn from_args(args: &RequestArgs) -> Result<Self> {
+        let headers = if args.hmac_headers.is_empty() {
+            DEFAULT_HEADERS.iter().map(|header| header.to_string()).collect()
+        } else {
+            args.hmac_headers.clone()
+        };
+        let headers = headers
+            .iter()
+            .map(|header| {
+                let (name, value) = header
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("--hmac-header must be NAME:VALUE, got '{}'", header))?;
+                let name = HeaderName::from_bytes(name.trim().as_bytes())
+                    .map_err(|_| anyhow!("Invalid --hmac-header name '{}'", name))?;
+                Ok((name, value.to_string()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Signer {
+            template: args.hmac_template.clone().unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+            headers,
+            encoding: args.hmac_encoding.unwrap_or_default(),
+        })
+    }
+}
+
+/// The values a template can refer to.
+struct Context<'a> {
+    request: &'a Request,
+    body: &'a [u8],
+    key_id: Option<&'a str>,
+    time: OffsetDateTime,
+    /// Only known once the string to sign is signed
+    signature: Option<&'a str>,
+}
+
+impl Context<'_> {
+    fn value(&self, name: &str) -> Result<Vec<u8>> {
+        let url = self.request.url();
+        let value = match name {
+            "method" => self.request.method().as_str().to_string(),
+            "path" => match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            },
+            "query" => url.query().unwrap_or_default().to_string(),
+            "host" => match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{}:{}", host, port),
+                (host, None) => host.unwrap_or_default().to_string(),
+                (None, Some(_)) => String::new(),
+            },
+            "timestamp" => self.time.unix_timestamp().to_string(),
+            "date" => self.time.format(&Rfc3339)?,
+            "body" => return Ok(self.body.to_vec()),
+            "body_sha256" => Sha256::digest(self.body).iter().map(|b| format!("{:02x}", b)).collect(),
+            "key_id" => self
+                .key_id
+                .ok_or_else(|| anyhow!("The HMAC template uses {{key_id}}, pass --auth KEY_ID:SECRET"))?
+                .to_string(),
+            "signature" => self
+                .signature
+                .ok_or_else(|| anyhow!("{{signature}} can only be used in --hmac-header values, not in the string to sign"))?
+                .to_string(),
+            _ => match name.strip_prefix("header:") {
+                Some(header) => self
+                    .request
+                    .headers()
+                    .get(header)
+                    .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                    .unwrap_or_default(),
+                None => return Err(anyhow!("Unknown placeholder {{{}}} in HMAC template", name)),
+            },
+        };
+        Ok(value.into_bytes())
+    }
+}
+
+/// Fills in `{placeholder}`s and the `\n`, `\r`, `\t` escapes; `\{` is a
+/// literal brace.
+fn render(template: &str, context: &Context) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => out.push(b'\n'),
+                Some('r') => out.push(b'\r'),
+                Some('t') => out.push(b'\t'),
+                Some(other) => out.extend(other.to_string().as_bytes()),
+                None => out.push(b'\\'),
+            },
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(anyhow!("Unclosed '{{' in HMAC template '{}'", template)),
+                    }
+                }
+                out.extend(context.value(&name)?);
+            }
+            c => out.extend(c.to_string().as_bytes()),
+        }
+    }
+    Ok(out)
+}
+
+/// Signs `request` in place: renders the string to sign from the final
+/// request, signs it with HMAC-SHA256 and adds the signer's headers.
+pub fn sign(
+    request: &mut Request,
+    key_id: Option<&str>,
+    secret: &str,
+    signer: &Signer,
+    time: OffsetDateTime,
+) -> Result<()> {
+    let body = match request.body() {
+        None => &[][..],
+        Some(body) => body
+            .as_bytes()
+            .ok_or_else(|| anyhow!("HMAC signing can't sign a streamed body (multipart upload)"))?,
+    };
+    let mut context = Context {
+        request,
+        body,
+        key_id,
+        time: time.to_offset(time::UtcOffset::UTC).replace_nanosecond(0)?,
+        signature: None,
+    };
+
+    let string_to_sign = render(&signer.template, &context)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(&string_to_sign);
+    let digest = mac.finalize().into_bytes();
+    let signature = match signer.encoding {
+        Encoding::Hex => digest.iter().map(|b| format!("{:02x}", b)).collect(),
+        Encoding::Base64 => general_purpose::STANDARD.encode(digest),
+    };
+
+    context.signature = Some(&signature);
+    let headers = signer
+        .headers
+        .iter()
+        .map(|(name, template)| Ok((name.clone(), HeaderValue::from_bytes(&render(template, &context)?)?)))
+        .collect::<Result<Vec<_>>>()?;
+    for (name, value) in headers {
+        request.headers_mut().insert(name, value);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn request(method: &str, url: &str, body: &str) -> Request {
+        let client = reqwest::Client::new();
+        client
+            .request(method.parse().unwrap(), url)
+            .header("X-Request-Id", "r-42")
+            .body(body.to_string())
+            .build()
+            .unwrap()
+    }
+
+    fn signer(argv: &[&str]) -> Signer {
+        let mut full = vec!["httprs", "https://api.example.com"];
+        full.extend_from_slice(argv);
+        Signer::from_args(&RequestArgs::try_parse_from(full).unwrap()).unwrap()
+    }
+
+    fn header(request: &Request, name: &str) -> String {
+        request.headers()[name].to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn signs_rfc4231_vector() {
+        // RFC 4231, test case 2
+        let mut req = request("POST", "https://api.example.com/hooks", "");
+        let signer = signer(&["--hmac-template", "what do ya want for nothing?"]);
+        sign(&mut req, None, "Jefe", &signer, OffsetDateTime::UNIX_EPOCH).unwrap();
+        assert_eq!(
+            header(&req, "x-signature"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(header(&req, "x-timestamp"), "0");
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let req = request("PUT", "https://api.example.com:8443/v1/orders?id=7", "{\"a\":1}");
+        let context = Context {
+            request: &req,
+            body: b"{\"a\":1}",
+            key_id: Some("k1"),
+            time: OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+            signature: None,
+        };
+        let rendered = render(
+            "{method}\\n{path}\\n{host}\\n{timestamp}\\n{date}\\n{key_id}\\n{header:x-request-id}\\n\\{body\\}={body}",
+            &context,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(rendered).unwrap(),
+            "PUT\n/v1/orders?id=7\napi.example.com:8443\n1700000000\n2023-11-14T22:13:20Z\nk1\nr-42\n{body}={\"a\":1}"
+        );
+
+        let err = render("{signature}", &context).unwrap_err();
+        assert!(err.to_string().contains("only be used in --hmac-header values"));
+        assert!(render("{nope}", &context).is_err());
+        assert!(render("{method", &context).is_err());
+        assert!(render("{key_id}", &Context { key_id: None, ..context }).is_err());
+    }
+
+    #[test]
+    fn custom_headers_and_encoding() {
+        let mut req = request("POST", "https://api.example.com/hooks", "payload");
+        let signer = signer(&[
+            "--hmac-template",
+            "{timestamp}.{body}",
+            "--hmac-header",
+            "Webhook-Signature:t={timestamp},v1={signature}",
+            "--hmac-encoding",
+            "base64",
+        ]);
+        let time = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        sign(&mut req, None, "whsec", &signer, time).unwrap();
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"whsec").unwrap();
+        mac.update(b"1700000000.payload");
+        let expected = general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+        assert_eq!(header(&req, "webhook-signature"), format!("t=1700000000,v1={}", expected));
+        assert!(req.headers().get("x-signature").is_none());
+        assert!(req.headers().get("x-timestamp").is_none());
+    }
+
+    #[test]
+    fn hmac_credentials() {
+        assert_eq!(
+            HmacScheme.credentials("k1:s3cret".parse().unwrap()).unwrap(),
+            Auth::Hmac {
+                key_id: Some("k1".into()),
+                secret: "s3cret".into()
+            }
+        );
+        assert_eq!(
+            HmacScheme.credentials("s3cret".parse().unwrap()).unwrap(),
+            Auth::Hmac {
+                key_id: None,
+                secret: "s3cret".into()
+            }
+        );
+        let args = RequestArgs::try_parse_from(["httprs", "x", "--hmac-header", "no-colon"]).unwrap();
+        assert!(Signer::from_args(&args).is_err());
+    }
+}
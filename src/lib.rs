@@ -1,13 +1,27 @@
 pub mod auth;
+pub mod aws;
 pub mod cli;
+pub mod config;
+pub mod digest;
+pub mod cookies;
+pub mod credentials;
 pub mod download;
+pub mod hmac_auth;
+pub mod nested;
+pub mod netrc;
+pub mod oauth;
+pub mod paths;
+pub mod proxy;
 pub mod request;
 pub mod response;
+pub mod session;
 pub mod client;
 pub mod timing;
+pub mod tls;
 
 // Re-export commonly used types
 pub use auth::Auth;
 pub use cli::{Cli, Command, OutputFilter, RequestArgs, RequestItem};
 pub use client::build_client;
 pub use request::execute_request;
+pub use session::Session;
@@ -1,19 +1,28 @@
 use anyhow::Result;
-use clap::Parser;
 use colored::Colorize;
-use httprs::{build_client, execute_request, Cli};
+use httprs::cookies::{open_jar, save_jar};
+use httprs::{build_client, execute_request, tls, Cli, Session};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let args = cli.command.args();
+    let mut cli = Cli::parse_args()?;
+
+    let mut session = Session::from_args(cli.command.args())?;
+    if let Some(ref session) = session {
+        session.apply(cli.command.args_mut());
+    }
+    let cookie_jar = open_jar(cli.command.args(), session.as_ref())?;
 
-    let client = build_client(args)?;
+    let args = cli.command.args();
+    let tls_config = tls::client_config(args)?;
+    let client = build_client(args, cookie_jar.clone(), tls_config.clone())?;
 
-    if let Err(e) = execute_request(&cli, &client).await {
+    if let Err(e) = execute_request(&cli, &client, &tls_config).await {
         eprintln!("\n{} {}\n", "Error:".red().bold(), e);
 
         let error_msg = e.to_string();
+        // TLS problems are only named in the error's sources
+        let error_chain = format!("{:#}", e);
 
         if error_msg.contains("dns error") || error_msg.contains("failed to lookup") {
             eprintln!("{}", "💡 Possible causes:".yellow());
@@ -29,6 +38,30 @@ async fn main() -> Result<()> {
             eprintln!("   - Server is not running");
             eprintln!("   - Wrong port number");
             eprintln!("   - Firewall blocking the connection");
+        } else if error_chain.contains("tunnel error") || error_chain.contains("socks proxy") {
+            eprintln!("{}", "💡 Proxy problem:".yellow());
+            eprintln!("   - {}", e.root_cause());
+            eprintln!("   - Check the proxy (--proxy, HTTPS_PROXY, ALL_PROXY) and --proxy-auth");
+            eprintln!("   - Reach this host directly with --no-proxy <host>");
+        } else if error_chain.contains("CertificateRequired") {
+            eprintln!("{}", "💡 The server requires a client certificate:".yellow());
+            eprintln!("   - Pass it with --cert (and --cert-key) or --cert-p12");
+        } else if let Some(mismatch) = tls::pin_mismatch(&e) {
+            eprintln!("{}", "💡 Certificate pin mismatch:".yellow());
+            eprintln!("   - None of the server's public keys match --pin; it served:");
+            for pin in &mismatch.served {
+                eprintln!("     {}", pin);
+            }
+            eprintln!("   - If the key was rotated on purpose, update the pin");
+        } else if error_chain.contains("certificate") {
+            eprintln!("{}", "💡 TLS certificate problem:".yellow());
+            eprintln!("   - {}", e.root_cause());
+            eprintln!("   - Trust a private CA with --verify=<ca-bundle.pem>");
+            eprintln!("   - Skip verification (self-signed local stacks) with --verify=no");
+        } else if error_chain.contains("fatal alert") || error_chain.contains("PeerIncompatible") {
+            eprintln!("{}", "💡 TLS handshake failed:".yellow());
+            eprintln!("   - {}", e.root_cause());
+            eprintln!("   - Check that the server supports the --ssl version");
         } else if error_msg.contains("No such file") {
             eprintln!("{}", "💡 File not found:".yellow());
             eprintln!("   - Check if the file path is correct");
@@ -37,5 +70,13 @@ async fn main() -> Result<()> {
         std::process::exit(1);
     };
 
+    if let (Some(session), Some(jar)) = (&mut session, &cookie_jar) {
+        session.update(cli.command.args(), jar);
+        session.save()?;
+    }
+    if let Some(jar) = &cookie_jar {
+        save_jar(cli.command.args(), jar)?;
+    }
+
     Ok(())
 }
@@ -0,0 +1,441 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+/// What to do when the same body key is given more than once.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum DuplicateKeys {
+    /// Collect repeated values into an array: tag=a tag=b -> {"tag": ["a", "b"]}
+    #[default]
+    Array,
+    /// Reject repeated keys
+    Error,
+}
+
+/// How many `null` slots an explicit index may pad an array with, so a typo
+/// like `a[999999999999]` is an error rather than a huge allocation.
+const MAX_INDEX_GAP: usize = 1000;
+
+/// One step of a nested body key such as `user[roles][]` or `items[0][id]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    // object key: "user", "[name]"
+    Key(String),
+    // array index: "[0]"
+    Index(usize),
+    // append to array: "[]"
+    Append,
+}
+
+/// Parses a body key into path segments.
+///
+/// `user[name]` -> [Key(user), Key(name)]
+/// `user[roles][]` -> [Key(user), Key(roles), Append]
+/// `items[0][id]` -> [Key(items), Index(0), Key(id)]
+/// `[]` -> [Append] (top-level array)
+/// `a\[b\]` -> [Key(a[b])] (escaped brackets are literal)
+pub fn parse_path(key: &str) -> Result<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+    let mut chars = key.chars().peekable();
+
+    let mut head = String::new();
+    while let Some(&c) = chars.peek() {
+        if c == '[' {
+            break;
+        }
+        if c == ']' {
+            return Err(anyhow!("Unexpected ']' in body key: {}", key));
+        }
+        chars.next();
+        head.push(unescape_next(c, &mut chars));
+    }
+    if !head.is_empty() {
+        segments.push(PathSegment::Key(head));
+    }
+
+    while let Some(c) = chars.next() {
+        if c != '[' {
+            return Err(anyhow!("Unexpected '{}' after ']' in body key: {}", c, key));
+        }
+
+        let mut inner = String::new();
+        let mut closed = false;
+        while let Some(c) = chars.next() {
+            match c {
+                ']' => {
+                    closed = true;
+                    break;
+                }
+                '[' => return Err(anyhow!("Unexpected '[' inside brackets in body key: {}", key)),
+                _ => inner.push(unescape_next(c, &mut chars)),
+            }
+        }
+        if !closed {
+            return Err(anyhow!("Unclosed '[' in body key: {}", key));
+        }
+
+        segments.push(if inner.is_empty() {
+            PathSegment::Append
+        } else if let Ok(index) = inner.parse::<usize>() {
+            PathSegment::Index(index)
+        } else {
+            PathSegment::Key(inner)
+        });
+    }
+
+    if segments.is_empty() {
+        return Err(anyhow!("Body key cannot be empty: {}", key));
+    }
+    Ok(segments)
+}
+
+/// Resolves `\[`, `\]` and `\\` escapes: returns the escaped character when `c`
+/// is a backslash followed by one of them, otherwise `c` itself.
+fn unescape_next(c: char, chars: &mut std::iter::Peekable<std::str::Chars>) -> char {
+    if c == '\\'
+        && let Some(&next) = chars.peek().filter(|next| matches!(next, '[' | ']' | '\\'))
+    {
+        chars.next();
+        return next;
+    }
+    c
+}
+
+/// Removes path escapes from a body key used verbatim, e.g. as a form field name.
+pub fn unescape_key(key: &str) -> String {
+    let mut chars = key.chars().peekable();
+    let mut out = String::with_capacity(key.len());
+    while let Some(c) = chars.next() {
+        out.push(unescape_next(c, &mut chars));
+    }
+    out
+}
+
+/// Formats path segments back into `a[b][0][]` form for error messages.
+pub fn display_path(segments: &[PathSegment]) -> String {
+    let mut out = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        match segment {
+            PathSegment::Key(key) if i == 0 => out.push_str(key),
+            PathSegment::Key(key) => out.push_str(&format!("[{}]", key)),
+            PathSegment::Index(index) => out.push_str(&format!("[{}]", index)),
+            PathSegment::Append => out.push_str("[]"),
+        }
+    }
+    out
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Inserts `value` into `root` at `path`, creating objects and arrays on the way.
+///
+/// Missing array slots before an explicit index are filled with `null`, up to
+/// [`MAX_INDEX_GAP`] of them.
+/// Using the same key as two different container types is an error; a key
+/// that already holds a value is handled according to `duplicates`.
+pub fn insert(
+    root: &mut Value,
+    path: &[PathSegment],
+    value: Value,
+    duplicates: DuplicateKeys,
+) -> Result<()> {
+    insert_at(root, path, 0, value, duplicates)
+}
+
+fn insert_at(
+    target: &mut Value,
+    path: &[PathSegment],
+    depth: usize,
+    value: Value,
+    duplicates: DuplicateKeys,
+) -> Result<()> {
+    let Some(segment) = path.get(depth) else {
+        return set_leaf(target, path, value, duplicates);
+    };
+
+    let expected = match segment {
+        PathSegment::Key(_) => "an object",
+        PathSegment::Index(_) | PathSegment::Append => "an array",
+    };
+    if target.is_null() {
+        *target = match segment {
+            PathSegment::Key(_) => Value::Object(Map::new()),
+            PathSegment::Index(_) | PathSegment::Append => Value::Array(Vec::new()),
+        };
+    }
+    let conflict = |target: &Value| {
+        anyhow!(
+            "Conflicting body paths: '{}' is used as both {} and {}",
+            if depth == 0 {
+                "<root>".to_string()
+            } else {
+                display_path(&path[..depth])
+            },
+            type_name(target),
+            expected
+        )
+    };
+
+    match segment {
+        PathSegment::Key(key) => {
+            let Value::Object(map) = target else {
+                return Err(conflict(target));
+            };
+            let child = map.entry(key.clone()).or_insert(Value::Null);
+            insert_at(child, path, depth + 1, value, duplicates)
+        }
+        PathSegment::Index(index) => {
+            let Value::Array(items) = target else {
+                return Err(conflict(target));
+            };
+            if index.saturating_sub(items.len()) > MAX_INDEX_GAP {
+                return Err(anyhow!(
+                    "Array index {} in body key '{}' is too large: the array has {} items",
+                    index,
+                    display_path(&path[..=depth]),
+                    items.len()
+                ));
+            }
+            if items.len() <= *index {
+                items.resize(index + 1, Value::Null);
+            }
+            insert_at(&mut items[*index], path, depth + 1, value, duplicates)
+        }
+        PathSegment::Append => {
+            let Value::Array(items) = target else {
+                return Err(conflict(target));
+            };
+            items.push(Value::Null);
+            let last = items.len() - 1;
+            insert_at(&mut items[last], path, depth + 1, value, duplicates)
+        }
+    }
+}
+
+fn set_leaf(
+    target: &mut Value,
+    path: &[PathSegment],
+    value: Value,
+    duplicates: DuplicateKeys,
+) -> Result<()> {
+    match (target, duplicates) {
+        (target @ Value::Null, _) => {
+            *target = value;
+            Ok(())
+        }
+        (Value::Object(_), _) => Err(anyhow!(
+            "Conflicting body paths: '{}' is already an object and cannot be set to a value",
+            display_path(path)
+        )),
+        (_, DuplicateKeys::Error) => Err(anyhow!(
+            "Duplicate body key '{}'",
+            display_path(path)
+        )),
+        (Value::Array(items), DuplicateKeys::Array) => {
+            items.push(value);
+            Ok(())
+        }
+        (target, DuplicateKeys::Array) => {
+            let first = target.take();
+            *target = Value::Array(vec![first, value]);
+            Ok(())
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn build(pairs: &[(&str, Value)]) -> Result<Value> {
+        let mut root = Value::Null;
+        for (key, value) in pairs {
+            insert(&mut root, &parse_path(key)?, value.clone(), DuplicateKeys::Array)?;
+        }
+        Ok(root)
+    }
+
+    #[test]
+    fn parse_path_plain_key() {
+        assert_eq!(
+            parse_path("name").unwrap(),
+            vec![PathSegment::Key("name".into())]
+        );
+    }
+
+    #[test]
+    fn parse_path_nested() {
+        assert_eq!(
+            parse_path("user[roles][]").unwrap(),
+            vec![
+                PathSegment::Key("user".into()),
+                PathSegment::Key("roles".into()),
+                PathSegment::Append,
+            ]
+        );
+
+        assert_eq!(
+            parse_path("items[0][id]").unwrap(),
+            vec![
+                PathSegment::Key("items".into()),
+                PathSegment::Index(0),
+                PathSegment::Key("id".into()),
+            ]
+        );
+
+        assert_eq!(parse_path("[]").unwrap(), vec![PathSegment::Append]);
+    }
+
+    #[test]
+    fn parse_path_errors() {
+        assert!(parse_path("user[name").is_err());
+        assert!(parse_path("user]").is_err());
+        assert!(parse_path("user[name]x").is_err());
+        assert!(parse_path("user[a[b]]").is_err());
+        assert!(parse_path("").is_err());
+    }
+
+    #[test]
+    fn parse_path_escaped_brackets() {
+        assert_eq!(
+            parse_path("a\\[0\\]").unwrap(),
+            vec![PathSegment::Key("a[0]".into())]
+        );
+        assert_eq!(
+            parse_path("meta[x\\]y]").unwrap(),
+            vec![PathSegment::Key("meta".into()), PathSegment::Key("x]y".into())]
+        );
+        assert_eq!(
+            parse_path("dir\\\\[a]").unwrap(),
+            vec![PathSegment::Key("dir\\".into()), PathSegment::Key("a".into())]
+        );
+    }
+
+    #[test]
+    fn unescape_key_works() {
+        assert_eq!(unescape_key("user[name]"), "user[name]");
+        assert_eq!(unescape_key("a\\[0\\]"), "a[0]");
+        assert_eq!(unescape_key("dir\\\\x"), "dir\\x");
+        assert_eq!(unescape_key("a\\b"), "a\\b");
+    }
+
+    #[test]
+    fn display_path_round_trip() {
+        for key in ["user", "user[name]", "items[0][id]", "tags[]", "[]"] {
+            assert_eq!(display_path(&parse_path(key).unwrap()), key);
+        }
+    }
+
+    #[test]
+    fn insert_nested_objects_and_arrays() {
+        let body = build(&[
+            ("user[name]", json!("alice")),
+            ("user[roles][]", json!("admin")),
+            ("user[roles][]", json!("dev")),
+            ("items[0][id]", json!(7)),
+            ("items[1][id]", json!(8)),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            body,
+            json!({
+                "user": {"name": "alice", "roles": ["admin", "dev"]},
+                "items": [{"id": 7}, {"id": 8}]
+            })
+        );
+    }
+
+    #[test]
+    fn insert_pads_sparse_indexes_with_null() {
+        let body = build(&[("list[2]", json!("c"))]).unwrap();
+        assert_eq!(body, json!({"list": [null, null, "c"]}));
+    }
+
+    #[test]
+    fn insert_rejects_huge_indexes() {
+        let err = build(&[("a[18446744073709551615]", json!("x"))]).unwrap_err();
+        assert!(err.to_string().contains("Array index 18446744073709551615 in body key 'a[18446744073709551615]' is too large"));
+
+        let err = build(&[("a[999999999999]", json!("x"))]).unwrap_err();
+        assert!(err.to_string().contains("the array has 0 items"));
+
+        // Padding up to the limit is still fine
+        assert!(build(&[("a[1000]", json!("x"))]).is_ok());
+        assert!(build(&[("a[1001]", json!("x"))]).is_err());
+    }
+
+    #[test]
+    fn insert_top_level_array() {
+        let body = build(&[("[]", json!(1)), ("[]", json!(2))]).unwrap();
+        assert_eq!(body, json!([1, 2]));
+    }
+
+    #[test]
+    fn insert_conflicting_paths() {
+        // object vs array
+        let err = build(&[("a[x]", json!(1)), ("a[]", json!(2))]).unwrap_err();
+        assert!(err.to_string().contains("'a' is used as both an object and an array"));
+
+        // scalar vs object
+        let err = build(&[("a", json!(1)), ("a[b]", json!(2))]).unwrap_err();
+        assert!(err.to_string().contains("'a' is used as both a number and an object"));
+
+        // object vs scalar
+        assert!(build(&[("a[b]", json!(1)), ("a", json!(2))]).is_err());
+
+        // top-level object vs array
+        assert!(build(&[("a", json!(1)), ("[]", json!(2))]).is_err());
+    }
+
+    #[test]
+    fn insert_duplicate_keys_as_array() {
+        let body = build(&[
+            ("tag", json!("a")),
+            ("tag", json!("b")),
+            ("tag", json!("c")),
+            ("user[id]", json!(1)),
+            ("user[id]", json!(2)),
+        ])
+        .unwrap();
+        assert_eq!(body, json!({"tag": ["a", "b", "c"], "user": {"id": [1, 2]}}));
+    }
+
+    #[test]
+    fn insert_duplicate_keys_error() {
+        let mut root = Value::Null;
+        let path = parse_path("tag").unwrap();
+        insert(&mut root, &path, json!("a"), DuplicateKeys::Error).unwrap();
+        let err = insert(&mut root, &path, json!("b"), DuplicateKeys::Error).unwrap_err();
+        assert!(err.to_string().contains("Duplicate body key 'tag'"));
+    }
+
+    #[test]
+    fn insert_preserves_key_order() {
+        let body = build(&[
+            ("zeta", json!(1)),
+            ("alpha", json!(2)),
+            ("mid[b]", json!(3)),
+            ("mid[a]", json!(4)),
+        ])
+        .unwrap();
+        assert_eq!(
+            serde_json::to_string(&body).unwrap(),
+            r#"{"zeta":1,"alpha":2,"mid":{"b":3,"a":4}}"#
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::auth::Auth;
+
+/// Credentials for one `machine` (or the `default` entry) of a `.netrc` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Machine {
+    pub login: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Netrc {
+    machines: Vec<(String, Machine)>,
+    default: Option<Machine>,
+}
+
+/// `$NETRC`, or `~/.netrc`.
+pub fn netrc_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("NETRC") {
+        return Some(PathBuf::from(path));
+    }
+    dirs::home_dir().map(|home| home.join(".netrc"))
+}
+
+/// Basic auth for `host` from the user's `.netrc`, if it has an entry.
+///
+/// Problems with the file are reported as warnings rather than failing the
+/// request, since the file is often shared with other tools.
+pub fn lookup(host: &str) -> Option<Auth> {
+    let path = netrc_path()?;
+    if !path.exists() {
+        return None;
+    }
+    warn_if_world_readable(&path);
+
+    let netrc = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow!("{}", e))
+        .and_then(|text| Netrc::parse(&text));
+    match netrc {
+        Ok(netrc) => netrc.auth_for(host),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("⚠️  Warning: Ignoring {}: {}", path.display(), e).yellow()
+            );
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn warn_if_world_readable(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(metadata) = std::fs::metadata(path)
+        && metadata.permissions().mode() & 0o004 != 0
+    {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️  Warning: {} is readable by other users; run `chmod 600 {}`",
+                path.display(),
+                path.display()
+            )
+            .yellow()
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_world_readable(_path: &Path) {}
+
+/// Splits a `.netrc` file into tokens, dropping `#` comment lines and the
+/// bodies of `macdef` macros (which run until the next blank line). A `#`
+/// later in a line is part of a token, e.g. `password #abc123`.
+fn tokenize(text: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut chars = line.chars().peekable();
+        let mut in_macdef = false;
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            let Some(&first) = chars.peek() else {
+                break;
+            };
+
+            let mut token = String::new();
+            if first == '"' {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => token.extend(chars.next()),
+                        Some(c) => token.push(c),
+                        None => return Err(anyhow!("unterminated quoted string")),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    token.push(c);
+                }
+            }
+
+            // `macdef NAME` is followed by the macro body on the next lines
+            if tokens.last().is_some_and(|last| last == "macdef") {
+                tokens.pop();
+                in_macdef = true;
+                break;
+            }
+            tokens.push(token);
+        }
+        if in_macdef {
+            for body in lines.by_ref() {
+                if body.trim().is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+impl Netrc {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut netrc = Netrc::default();
+        let mut current: Option<(Option<String>, Machine)> = None;
+        let mut tokens = tokenize(text)?.into_iter();
+
+        while let Some(token) = tokens.next() {
+            let mut value = |token: &str| {
+                tokens
+                    .next()
+                    .ok_or_else(|| anyhow!("missing value after '{}'", token))
+            };
+            match token.as_str() {
+                "machine" => {
+                    netrc.finish(current.take());
+                    current = Some((Some(value(&token)?), Machine::default()));
+                }
+                "default" => {
+                    netrc.finish(current.take());
+                    current = Some((None, Machine::default()));
+                }
+                "login" | "password" | "account" => {
+                    let value = value(&token)?;
+                    let Some((_, machine)) = current.as_mut() else {
+                        return Err(anyhow!("'{}' outside of a machine entry", token));
+                    };
+                    match token.as_str() {
+                        "login" => machine.login = Some(value),
+                        "password" => machine.password = Some(value),
+                        _ => {}
+                    }
+                }
+                other => return Err(anyhow!("unexpected token '{}'", other)),
+            }
+        }
+        netrc.finish(current);
+        Ok(netrc)
+    }
+
+    fn finish(&mut self, entry: Option<(Option<String>, Machine)>) {
+        match entry {
+            Some((Some(name), machine)) => self.machines.push((name, machine)),
+            // Only the first `default` counts
+            Some((None, machine)) if self.default.is_none() => self.default = Some(machine),
+            _ => {}
+        }
+    }
+
+    /// The first entry for `host`, or the `default` entry.
+    pub fn machine(&self, host: &str) -> Option<&Machine> {
+        self.machines
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(host))
+            .map(|(_, machine)| machine)
+            .or(self.default.as_ref())
+    }
+
+    pub fn auth_for(&self, host: &str) -> Option<Auth> {
+        let machine = self.machine(host)?;
+        Some(Auth::Basic {
+            username: machine.login.clone()?,
+            password: machine.password.clone(),
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn basic(username: &str, password: Option<&str>) -> Auth {
+        Auth::Basic {
+            username: username.into(),
+            password: password.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn parse_machines_and_default() {
+        let netrc = Netrc::parse(
+            "machine api.example.com login alice password s3cret\n\
+             # a comment\n\
+             machine other.example.com\n  login bob\n  account ops\n  password \"p w\\\"d\"\n\
+             default login anonymous password guest\n",
+        )
+        .unwrap();
+
+        assert_eq!(netrc.auth_for("api.example.com"), Some(basic("alice", Some("s3cret"))));
+        assert_eq!(netrc.auth_for("API.example.com"), Some(basic("alice", Some("s3cret"))));
+        assert_eq!(netrc.auth_for("other.example.com"), Some(basic("bob", Some("p w\"d"))));
+        assert_eq!(netrc.auth_for("unknown.com"), Some(basic("anonymous", Some("guest"))));
+    }
+
+    #[test]
+    fn hash_inside_a_line_is_not_a_comment() {
+        let netrc = Netrc::parse(
+            "  # indented comment
+             machine api.example.com login alice password #abc123
+             machine other.example.com login bob password a#b
+",
+        )
+        .unwrap();
+        assert_eq!(netrc.auth_for("api.example.com"), Some(basic("alice", Some("#abc123"))));
+        assert_eq!(netrc.auth_for("other.example.com"), Some(basic("bob", Some("a#b"))));
+    }
+
+    #[test]
+    fn parse_skips_macdef_bodies() {
+        let netrc = Netrc::parse(
+            "machine ftp.example.com login alice password one\n\
+             macdef init\n\
+             cd /pub\n\
+             machine fake login mallory password evil\n\
+             \n\
+             machine api.example.com login bob password two\n",
+        )
+        .unwrap();
+
+        assert_eq!(netrc.auth_for("ftp.example.com"), Some(basic("alice", Some("one"))));
+        assert_eq!(netrc.auth_for("api.example.com"), Some(basic("bob", Some("two"))));
+        assert_eq!(netrc.auth_for("fake"), None);
+    }
+
+    #[test]
+    fn first_matching_entry_wins() {
+        let netrc = Netrc::parse(
+            "machine a.com login first password 1\nmachine a.com login second password 2",
+        )
+        .unwrap();
+        assert_eq!(netrc.auth_for("a.com"), Some(basic("first", Some("1"))));
+        assert_eq!(netrc.auth_for("b.com"), None);
+
+        // An entry without a login gives no credentials
+        let netrc = Netrc::parse("machine a.com password 1").unwrap();
+        assert_eq!(netrc.auth_for("a.com"), None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Netrc::parse("login alice").is_err());
+        assert!(Netrc::parse("machine").is_err());
+        assert!(Netrc::parse("machine a.com login").is_err());
+        assert!(Netrc::parse("machine a.com user alice").is_err());
+        assert!(Netrc::parse("machine a.com password \"open").is_err());
+    }
+}
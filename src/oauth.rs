@@ -0,0 +1,346 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use reqwest::{header, Client, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use time::OffsetDateTime;
+
+use crate::auth::{Auth, AuthScheme};
+use crate::cli::RequestArgs;
+use crate::paths::{config_dir, write_private};
+
+/// Tokens are refreshed this many seconds before they expire.
+const EXPIRY_MARGIN: i64 = 30;
+
+/// OAuth2 grant used to get an access token.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Grant {
+    #[default]
+    ClientCredentials,
+    Password,
+    RefreshToken,
+}
+
+impl Grant {
+    fn as_str(self) -> &'static str {
+        match self {
+            Grant::ClientCredentials => "client_credentials",
+            Grant::Password => "password",
+            Grant::RefreshToken => "refresh_token",
+        }
+    }
+}
+
+/// Everything needed to ask the token endpoint for an access token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthConfig {
+    pub token_url: String,
+    pub grant: Grant,
+    pub scope: Option<String>,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    /// Resource owner credentials for the password grant
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Initial refresh token for the refresh token grant
+    pub refresh_token: Option<String>,
+}
+
+impl OAuthConfig {
+    pub fn from_args(args: &RequestArgs, client_id: &str, client_secret: Option<&str>) -> Result<Self> {
+        let token_url = args
+            .oauth_token_url
+            .clone()
+            .ok_or_else(|| anyhow!("OAuth2 needs the token endpoint: --oauth-token-url URL"))?;
+        let grant = args.oauth_grant.unwrap_or_default();
+
+        let (username, password) = match args.oauth_user.as_deref().map(|user| user.split_once(':')) {
+            Some(Some((username, password))) => (Some(username.to_string()), Some(password.to_string())),
+            Some(None) => (args.oauth_user.clone(), None),
+            None => (None, None),
+        };
+        if grant == Grant::Password && username.is_none() {
+            return Err(anyhow!("The password grant needs --oauth-user USER:PASSWORD"));
+        }
+        if grant == Grant::RefreshToken && args.oauth_refresh_token.is_none() {
+            return Err(anyhow!("The refresh token grant needs --oauth-refresh-token TOKEN"));
+        }
+
+        Ok(OAuthConfig {
+            token_url,
+            grant,
+            scope: args.oauth_scope.clone(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.map(str::to_string),
+            username,
+            password,
+            refresh_token: args.oauth_refresh_token.clone(),
+        })
+    }
+
+    /// The cache file for this client, grant, scope and user.
+    fn cache_path(&self) -> Result<PathBuf> {
+        let key = [
+            self.token_url.as_str(),
+            self.grant.as_str(),
+            self.client_id.as_str(),
+            self.scope.as_deref().unwrap_or_default(),
+            self.username.as_deref().unwrap_or_default(),
+        ]
+        .join("\n");
+        let hash: String = Sha256::digest(key.as_bytes())
+            .iter()
+            .take(16)
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Ok(config_dir()?.join("oauth").join(format!("{}.json", hash)))
+    }
+
+    /// Form fields of a token request; `refresh_token` overrides the grant.
+    fn token_request(&self, refresh_token: Option<&str>) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        match refresh_token.or(self.refresh_token.as_deref().filter(|_| self.grant == Grant::RefreshToken)) {
+            Some(token) => {
+                fields.push(("grant_type", "refresh_token".to_string()));
+                fields.push(("refresh_token", token.to_string()));
+            }
+            None => {
+                fields.push(("grant_type", self.grant.as_str().to_string()));
+                if self.grant == Grant::Password {
+                    fields.push(("username", self.username.clone().unwrap_or_default()));
+                    fields.push(("password", self.password.clone().unwrap_or_default()));
+                }
+            }
+        }
+        if let Some(scope) = &self.scope {
+            fields.push(("scope", scope.clone()));
+        }
+        // Public clients identify themselves in the body instead of with Basic auth
+        if self.client_secret.is_none() {
+            fields.push(("client_id", self.client_id.clone()));
+        }
+        fields
+    }
+}
+
+/// `--auth-type oauth2`, with the client credentials as `--auth ID[:SECRET]`.
+pub struct OAuth2Scheme;
+
+impl AuthScheme for OAuth2Scheme {
+    fn name(&self) -> &'static str {
+        "oauth2"
+    }
+
+    fn credentials(&self, auth: Auth) -> Result<Auth> {
+        match auth {
+            Auth::Basic { username, password } => Ok(Auth::OAuth2 {
+                client_id: username,
+                client_secret: password,
+            }),
+            auth @ Auth::OAuth2 { .. } => Ok(auth),
+            _ => Err(anyhow!("OAuth2 needs the client credentials as CLIENT_ID[:CLIENT_SECRET]")),
+        }
+    }
+
+    fn needs_credentials(&self) -> bool {
+        true
+    }
+}
+
+/// A token endpoint response (RFC 6749, section 5.1).
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+/// An access token as cached on disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedToken {
+    pub access_token: String,
+    /// Unix time; `None` when the server didn't say
+    pub expires_at: Option<i64>,
+    pub refresh_token: Option<String>,
+}
+
+impl CachedToken {
+    pub fn is_valid(&self, now: i64) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at - EXPIRY_MARGIN > now)
+    }
+}
+
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+fn load_cached(config: &OAuthConfig) -> Option<CachedToken> {
+    let data = std::fs::read_to_string(config.cache_path().ok()?).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn save_cached(config: &OAuthConfig, token: &CachedToken) -> Result<()> {
+    write_private(&config.cache_path()?, serde_json::to_string_pretty(token)?.as_bytes())
+}
+
+async fn request_token(client: &Client, config: &OAuthConfig, refresh_token: Option<&str>) -> Result<CachedToken> {
+    let mut request = client
+        .post(&config.token_url)
+        .header(header::ACCEPT, "application/json")
+        .form(&config.token_request(refresh_token));
+    if let Some(secret) = &config.client_secret {
+        request = request.basic_auth(&config.client_id, Some(secret));
+    }
+
+    let resp = request.send().await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        return Err(anyhow!("OAuth2 token request failed ({}): {}", status, body.trim()));
+    }
+    let token: TokenResponse = serde_json::from_str(&body)
+        .map_err(|e| anyhow!("Invalid OAuth2 token response: {}", e))?;
+    Ok(CachedToken {
+        access_token: token.access_token,
+        expires_at: token.expires_in.map(|seconds| now() + seconds),
+        refresh_token: token.refresh_token.or_else(|| refresh_token.map(str::to_string)),
+    })
+}
+
+/// A new access token: from the cached refresh token when there is one
+/// (falling back to the grant if it's rejected), else from the grant.
+pub async fn refresh(client: &Client, config: &OAuthConfig) -> Result<String> {
+    let cached_refresh = load_cached(config).and_then(|token| token.refresh_token);
+    let token = match cached_refresh {
+        Some(refresh_token) => match request_token(client, config, Some(&refresh_token)).await {
+            Ok(token) => token,
+            Err(_) => request_token(client, config, None).await?,
+        },
+        None => request_token(client, config, None).await?,
+    };
+    save_cached(config, &token)?;
+    Ok(token.access_token)
+}
+
+/// The cached access token while it is valid, otherwise a new one.
+pub async fn access_token(client: &Client, config: &OAuthConfig) -> Result<String> {
+    match load_cached(config) {
+        Some(token) if token.is_valid(now()) => Ok(token.access_token),
+        _ => refresh(client, config).await,
+    }
+}
+
+/// Sends `request` (which carries the current access token); on a 401 the
+/// token is refreshed once, with `token_client`, and the request is sent again.
+pub async fn send(client: &Client, request: Request, config: &OAuthConfig, token_client: &Client) -> Result<Response> {
+    let Some(mut retry) = request.try_clone() else {
+        // Streamed bodies can't be replayed, so no retry
+        return Ok(client.execute(request).await?);
+    };
+
+    let resp = client.execute(request).await?;
+    if resp.status() != StatusCode::UNAUTHORIZED {
+        return Ok(resp);
+    }
+
+    let token = refresh(token_client, config).await?;
+    retry.headers_mut().insert(
+        header::AUTHORIZATION,
+        header::HeaderValue::from_str(&format!("Bearer {}", token))?,
+    );
+    Ok(client.execute(retry).await?)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn parse_args(argv: &[&str]) -> RequestArgs {
+        RequestArgs::try_parse_from(argv).unwrap()
+    }
+
+    fn oauth_config(argv: &[&str], secret: Option<&str>) -> Result<OAuthConfig> {
+        let mut full = vec!["httprs", "https://api.example.com", "--oauth-token-url", "https://auth.example.com/token"];
+        full.extend_from_slice(argv);
+        OAuthConfig::from_args(&parse_args(&full), "my-client", secret)
+    }
+
+    #[test]
+    fn client_credentials_request() {
+        let config = oauth_config(&["--oauth-scope", "read write"], Some("s3cret")).unwrap();
+        assert_eq!(config.grant, Grant::ClientCredentials);
+        assert_eq!(
+            config.token_request(None),
+            vec![
+                ("grant_type", "client_credentials".to_string()),
+                ("scope", "read write".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn password_grant_request() {
+        let config = oauth_config(&["--oauth-grant", "password", "--oauth-user", "alice:pw"], None).unwrap();
+        assert_eq!(
+            config.token_request(None),
+            vec![
+                ("grant_type", "password".to_string()),
+                ("username", "alice".to_string()),
+                ("password", "pw".to_string()),
+                ("client_id", "my-client".to_string()),
+            ]
+        );
+        assert!(oauth_config(&["--oauth-grant", "password"], None).is_err());
+    }
+
+    #[test]
+    fn refresh_token_request() {
+        let config = oauth_config(&["--oauth-grant", "refresh-token", "--oauth-refresh-token", "rt1"], Some("s")).unwrap();
+        assert_eq!(
+            config.token_request(None),
+            vec![
+                ("grant_type", "refresh_token".to_string()),
+                ("refresh_token", "rt1".to_string()),
+            ]
+        );
+        // A newer refresh token from the cache takes precedence
+        assert_eq!(config.token_request(Some("rt2"))[1], ("refresh_token", "rt2".to_string()));
+        assert!(oauth_config(&["--oauth-grant", "refresh-token"], None).is_err());
+    }
+
+    #[test]
+    fn token_url_is_required() {
+        let args = parse_args(&["httprs", "https://api.example.com"]);
+        assert!(OAuthConfig::from_args(&args, "id", None).is_err());
+    }
+
+    #[test]
+    fn cache_key_depends_on_scope() {
+        let read = oauth_config(&["--oauth-scope", "read"], None).unwrap();
+        let write = oauth_config(&["--oauth-scope", "write"], None).unwrap();
+        assert_ne!(read.cache_path().unwrap(), write.cache_path().unwrap());
+        assert_eq!(read.cache_path().unwrap(), read.clone().cache_path().unwrap());
+    }
+
+    #[test]
+    fn cached_token_expiry() {
+        let token = CachedToken {
+            access_token: "t".into(),
+            expires_at: Some(1_000),
+            refresh_token: None,
+        };
+        assert!(token.is_valid(900));
+        assert!(!token.is_valid(980));
+        assert!(!token.is_valid(2_000));
+        assert!(CachedToken { expires_at: None, ..token }.is_valid(2_000));
+    }
+}
@@ -0,0 +1,36 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+/// httprs' configuration directory: `$HTTPRS_CONFIG_DIR`, or `httprs` under
+/// the platform config directory (`$XDG_CONFIG_HOME` / `~/.config` on Linux).
+pub fn config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("HTTPRS_CONFIG_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("httprs"))
+        .ok_or_else(|| anyhow!("Could not determine the config directory; set HTTPRS_CONFIG_DIR"))
+}
+
+/// Writes `contents` to `path`, creating parent directories. On Unix the file
+/// is only readable by the owner since it may hold credentials.
+pub fn write_private(path: &std::path::Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Failed to create directory '{}': {}", parent.display(), e))?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    use std::io::Write;
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|e| anyhow!("Failed to write '{}': {}", path.display(), e))
+}
@@ -1,13 +1,26 @@
 use anyhow::{anyhow, Result};
 use colored::Colorize;
 use reqwest::{Client, Url};
-use std::collections::HashMap;
-
-use crate::auth::apply_auth;
-use crate::cli::{Cli, RequestItem};
+use rustls::ClientConfig;
+use serde_json::{Map, Value};
+use std::io::IsTerminal;
+use tokio::io::AsyncReadExt;
+
+use crate::auth::{apply_auth, resolve_auth, Auth, AuthType};
+use crate::aws;
+use crate::cli::{Cli, RequestArgs, RequestItem};
+use crate::client::{build_plain_client, default_headers};
+use crate::credentials;
+use crate::digest;
 use crate::download::{determine_filename, download_file};
-use crate::response::print_resp;
+use crate::hmac_auth;
+use crate::nested::{insert, parse_path, unescape_key, DuplicateKeys};
+use crate::netrc;
+use crate::oauth::{self, Grant, OAuthConfig};
+use crate::proxy::Proxies;
+use crate::response::{print_certificates, print_resp};
 use crate::timing::RequestTimer;
+use crate::tls;
 
 #[derive(Debug, Default)]
 pub struct VerboseInfo {
@@ -17,6 +30,7 @@ pub struct VerboseInfo {
     query_params: Vec<(String, String)>,
     body: Option<String>,
     files: Vec<(String, String)>,
+    proxy: Option<String>,
 }
 
 impl VerboseInfo {
@@ -28,6 +42,7 @@ impl VerboseInfo {
             query_params: Vec::new(),
             body: None,
             files: Vec::new(),
+            proxy: None,
         }
     }
 
@@ -47,6 +62,10 @@ impl VerboseInfo {
         self.files.push((key, filepath));
     }
 
+    pub fn set_proxy(&mut self, proxy: String) {
+        self.proxy = Some(proxy);
+    }
+
     pub fn print(&self) {
         let full_url = if self.query_params.is_empty() {
             self.url.clone()
@@ -67,6 +86,10 @@ impl VerboseInfo {
             parsed_url.path().to_string()
         };
 
+        if let Some(proxy) = &self.proxy {
+            println!("{} {}", "Proxy:".cyan().bold(), proxy);
+        }
+
         println!(
             "{} {} {} {}",
             ">".cyan().bold(),
@@ -127,22 +150,115 @@ impl VerboseInfo {
     }
 }
 
+/// A multipart form field
+#[derive(Debug, Clone, PartialEq)]
+enum FormPart {
+    Text(String, String),
+    File(String, String),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BodyType {
     Json,
+    Form,
     Multipart,
+    Raw,
+}
+
+/// Where a raw (non key=value) request body comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawSource {
+    // --raw '<data>'
+    Data(String),
+    // @payload.json
+    File(String),
+    // piped stdin
+    Stdin,
+}
+
+/// Picks the raw body source, if any.
+///
+/// `--raw` and `@file` are explicit and conflict with each other and with body
+/// fields. Stdin is only read when it is piped, not ignored, and no other body
+/// was given.
+pub fn raw_body_source(args: &RequestArgs, stdin_piped: bool) -> Result<Option<RawSource>> {
+    let has_fields = args.items.iter().any(|item| {
+        matches!(
+            item,
+            RequestItem::Body(_, _) | RequestItem::RawJson(_, _) | RequestItem::FormFile(_, _)
+        )
+    });
+
+    let mut sources: Vec<RawSource> = args.raw.iter().cloned().map(RawSource::Data).collect();
+    for item in &args.items {
+        if let RequestItem::RawBodyFile(path) = item {
+            sources.push(RawSource::File(path.clone()));
+        }
+    }
+
+    if sources.len() > 1 {
+        return Err(anyhow!(
+            "Only one raw body source can be used: --raw or a single @file"
+        ));
+    }
+    if let Some(source) = sources.pop() {
+        if has_fields {
+            return Err(anyhow!(
+                "A raw request body cannot be combined with body fields or files"
+            ));
+        }
+        return Ok(Some(source));
+    }
+
+    if stdin_piped && !args.ignore_stdin && !has_fields {
+        return Ok(Some(RawSource::Stdin));
+    }
+    Ok(None)
+}
+
+/// Loads a raw body and infers its Content-Type: from the file extension for
+/// `@file`, `application/json` for data that parses as JSON, otherwise none.
+/// Empty stdin yields no body.
+pub async fn load_raw_body(source: &RawSource) -> Result<Option<(Vec<u8>, Option<String>)>> {
+    let data = match source {
+        RawSource::Data(data) => data.clone().into_bytes(),
+        RawSource::File(path) => {
+            let data = tokio::fs::read(path)
+                .await
+                .map_err(|e| anyhow!("Failed to read file '{}': {}", path, e))?;
+            let mime_type = mime_guess::from_path(path).first_or_octet_stream();
+            return Ok(Some((data, Some(mime_type.to_string()))));
+        }
+        RawSource::Stdin => {
+            let mut data = Vec::new();
+            tokio::io::stdin().read_to_end(&mut data).await?;
+            if data.is_empty() {
+                return Ok(None);
+            }
+            data
+        }
+    };
+
+    let content_type = serde_json::from_slice::<Value>(&data)
+        .is_ok()
+        .then(|| "application/json".to_string());
+    Ok(Some((data, content_type)))
 }
 
-pub fn detect_body_type(items: &[RequestItem]) -> Option<BodyType> {
+/// Files always force multipart; otherwise body fields are sent as JSON,
+/// or urlencoded when `form` (`--form`) is set.
+pub fn detect_body_type(items: &[RequestItem], form: bool) -> Option<BodyType> {
     let has_file = items
         .iter()
         .any(|item| matches!(item, RequestItem::FormFile(_, _)));
     let has_body = items
         .iter()
-        .any(|item| matches!(item, RequestItem::Body(_, _)));
+        .any(|item| matches!(item, RequestItem::Body(_, _) | RequestItem::RawJson(_, _)));
 
     if has_file {
         Some(BodyType::Multipart)
+    } else if has_body && form {
+        Some(BodyType::Form)
     } else if has_body {
         // Default to JSON for HTTPie compatibility
         Some(BodyType::Json)
@@ -151,11 +267,85 @@ pub fn detect_body_type(items: &[RequestItem]) -> Option<BodyType> {
     }
 }
 
-pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
+/// Builds the JSON body from `key=value` (string) and `key:=json` (raw JSON) items.
+///
+/// Keys may be nested paths (`user[name]`, `tags[]`, `items[0][id]`), which
+/// are merged into a single object or array tree. Fields keep command-line
+/// order; repeated keys are handled according to `duplicates`.
+pub fn build_json_body(items: &[RequestItem], duplicates: DuplicateKeys) -> Result<Value> {
+    let mut body = Value::Null;
+    for item in items {
+        let (key, value) = match item {
+            RequestItem::Body(key, value) => (key, Value::String(value.clone())),
+            RequestItem::RawJson(key, value) => (key, value.clone()),
+            _ => continue,
+        };
+        insert(&mut body, &parse_path(key)?, value, duplicates)?;
+    }
+    if body.is_null() {
+        body = Value::Object(Map::new());
+    }
+    Ok(body)
+}
+
+/// The `.netrc` credentials for `host`, found with `lookup`. An entry is a
+/// login and password, so it's only used for Basic and Digest auth (or when
+/// no `--auth-type` is given), and not with `--ignore-netrc` or `--auth-cmd`.
+fn netrc_auth(args: &RequestArgs, host: Option<&str>, lookup: impl FnOnce(&str) -> Option<Auth>) -> Option<Auth> {
+    let username_password = args
+        .auth_type
+        .is_none_or(|auth_type| auth_type == AuthType::BASIC || auth_type == AuthType::DIGEST);
+    if args.ignore_netrc || args.auth_cmd.is_some() || !username_password {
+        return None;
+    }
+    host.and_then(lookup)
+}
+
+pub async fn execute_request(cli: &Cli, client: &Client, tls_config: &ClientConfig) -> Result<()> {
     let command = &cli.command;
     let args = command.args();
     let method = command.method();
 
+    let target = Url::parse(&args.url).ok();
+    let proxies = Proxies::from_args(args)?;
+    let proxy = target.as_ref().and_then(|url| proxies.for_url(url));
+
+    // --tls-info reports the negotiated TLS version, cipher suite and ALPN,
+    // --print-cert the certificate chain too. Both come from a probe
+    // connection with the client's settings; if it fails the request still
+    // goes ahead and reports its own error
+    let timeout = std::time::Duration::from_secs(args.timeout);
+    let proxy_url = proxy.map(|proxy| &proxy.url);
+    let probe = match &target {
+        Some(url) if url.scheme() == "https" && args.print_cert => {
+            Some(tls::inspect(url, tls_config.clone(), proxy_url, timeout).await)
+        }
+        Some(url) if url.scheme() == "https" && args.tls_info => {
+            Some(tls::handshake(url, tls_config.clone(), proxy_url, timeout).await)
+        }
+        _ => None,
+    };
+    let mut handshake = match probe {
+        Some(Err(e)) => {
+            let shown = if args.print_cert { "certificate chain" } else { "TLS details" };
+            eprintln!(
+                "{}",
+                format!(
+                    "⚠️  Warning: No {} to show, the TLS probe connection failed: {}",
+                    shown,
+                    tls::failure_reason(&e)
+                )
+                .yellow()
+            );
+            None
+        }
+        probe => probe.and_then(Result::ok),
+    };
+    // A rejected chain is shown now, since the request is about to fail on it
+    if let Some(rejected) = handshake.take_if(|handshake| handshake.verify_error.is_some()) {
+        print_certificates(&rejected);
+    }
+
     let mut timer=if args.verbose{
         Some(RequestTimer::start())
     }else{
@@ -169,50 +359,167 @@ pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
     } else {
         None
     };
+    if let (Some(info), Some(proxy)) = (&mut verbose_info, proxy) {
+        info.set_proxy(proxy.describe());
+    }
 
-    //apply auth
-    req_builder = apply_auth(req_builder, &args.auth, &mut verbose_info);
+    // Show the client's default headers unless a request item overrides them
+    if let Some(ref mut info) = verbose_info {
+        for (name, value) in default_headers(args)?.iter() {
+            let overridden = args.items.iter().any(|item| match item {
+                RequestItem::Header(key, _) | RequestItem::EmptyHeader(key) => {
+                    key.eq_ignore_ascii_case(name.as_str())
+                }
+                _ => false,
+            });
+            if !overridden {
+                info.add_header(
+                    name.to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                );
+            }
+        }
+    }
 
-    let mut body = HashMap::new();
-    let mut form_fields = HashMap::new();
-    let mut files: Vec<(String, String)> = Vec::new();
+    //apply auth, falling back to ~/.netrc unless an Authorization header is given
+    let has_auth_header = args.items.iter().any(|item| match item {
+        RequestItem::Header(key, _) | RequestItem::EmptyHeader(key) => {
+            key.eq_ignore_ascii_case("authorization")
+        }
+        _ => false,
+    });
+    let host = Url::parse(&args.url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string));
+    let auth = match &args.auth {
+        None if !has_auth_header => netrc_auth(args, host.as_deref(), netrc::lookup),
+        auth => auth.clone(),
+    };
+    let from_netrc = args.auth.is_none() && auth.is_some();
+    // Secrets missing from --auth come from the credential helper, or else
+    // are asked for on the terminal (unless stdin is to be left alone); a
+    // .netrc entry is used as written
+    let auth = match &args.auth_cmd {
+        Some(command) if auth.is_some() || !has_auth_header => {
+            credentials::fill_from_helper(auth, command)?
+        }
+        _ => auth,
+    };
+    let auth = resolve_auth(auth, args.auth_type)?;
+    let auth = match &host {
+        Some(host) if !from_netrc => credentials::prompt_missing_password(auth, host, !args.ignore_stdin)?,
+        _ => auth,
+    };
+    if auth.is_none() && args.auth_type.is_some_and(|auth_type| auth_type.scheme().needs_credentials()) {
+        return Err(anyhow!("This --auth-type needs credentials: pass --auth"));
+    }
+
+    // OAuth2 requests carry the access token as a Bearer token, fetched with
+    // a client of its own since the token endpoint is another host
+    let oauth = match &auth {
+        Some(Auth::OAuth2 { client_id, client_secret }) => {
+            let mut config = OAuthConfig::from_args(args, client_id, client_secret.as_deref())?;
+            if let Some(username) = &config.username
+                && config.grant == Grant::Password
+                && config.password.is_none()
+                && !args.ignore_stdin
+            {
+                config.password = credentials::prompt_password(&format!("Password for {}: ", username));
+            }
+            Some((config, build_plain_client(args)?))
+        }
+        _ => None,
+    };
+    let auth = match &oauth {
+        Some((config, token_client)) => Some(Auth::Bearer(oauth::access_token(token_client, config).await?)),
+        None => auth,
+    };
+    req_builder = apply_auth(req_builder, &auth, &mut verbose_info);
+
+    // If it is GET/HEAD/OPTIONS, body items are ignored with a warning
+    let allows_body = !matches!(
+        method,
+        reqwest::Method::GET | reqwest::Method::HEAD | reqwest::Method::OPTIONS
+    );
+    let raw_source = if allows_body {
+        raw_body_source(args, !std::io::stdin().is_terminal())?
+    } else {
+        if args.raw.is_some() {
+            eprintln!(
+                "{}",
+                format!("⚠️  Warning: Ignoring --raw body in {} request", method).yellow()
+            );
+        }
+        None
+    };
+    let raw_body = match &raw_source {
+        Some(source) => load_raw_body(source).await?,
+        None => None,
+    };
+    let body_type = if raw_body.is_some() {
+        Some(BodyType::Raw)
+    } else {
+        detect_body_type(&args.items, args.form)
+    };
+    // Form text fields and files, in command-line order
+    let mut form_parts: Vec<FormPart> = Vec::new();
     let mut query_params: Vec<(String, String)> = Vec::new();
 
     for item in &args.items {
         match item {
+            // `Header:` unsets the header; defaults are dropped in build_client
+            RequestItem::Header(_, value) if value.is_empty() => {}
             RequestItem::Header(key, value) => {
                 req_builder = req_builder.header(key, value);
                 if let Some(ref mut info) = verbose_info {
                     info.add_header(key.clone(), value.clone());
                 }
             }
+            // Sent by the client's cookie provider, see build_client
+            RequestItem::Cookie(_, _) => {}
+            RequestItem::EmptyHeader(key) => {
+                req_builder = req_builder.header(key, "");
+                if let Some(ref mut info) = verbose_info {
+                    info.add_header(key.clone(), String::new());
+                }
+            }
             RequestItem::QueryParam(key, value) => {
                 query_params.push((key.clone(), value.clone()));
                 if let Some(ref mut info) = verbose_info {
                     info.add_query_param(key.clone(), value.clone());
                 }
             }
+            RequestItem::Body(key, _) | RequestItem::RawJson(key, _) if !allows_body => {
+                eprintln!(
+                    "{}",
+                    format!(
+                        "⚠️  Warning: Ignoring body parameter '{}' in {} request",
+                        key, method
+                    )
+                    .yellow()
+                );
+            }
             RequestItem::Body(key, value) => {
-                // If it is GET/HEAD/OPTIONS, Warnings
-                if matches!(
-                    method,
-                    reqwest::Method::GET | reqwest::Method::HEAD | reqwest::Method::OPTIONS
-                ) {
-                    eprintln!(
-                        "{}",
-                        format!(
-                            "⚠️  Warning: Ignoring body parameter '{}' in {} request",
-                            key, method
-                        )
-                        .yellow()
-                    );
-                } else {
-                    body.insert(key.clone(), value.clone());
-                    form_fields.insert(key.clone(), value.clone());
+                form_parts.push(FormPart::Text(unescape_key(key), value.clone()));
+            }
+            RequestItem::RawJson(key, _) => {
+                if body_type != Some(BodyType::Json) {
+                    return Err(anyhow!(
+                        "Raw JSON field '{}' can only be sent in a JSON body",
+                        key
+                    ));
                 }
             }
+            RequestItem::RawBodyFile(path) if !allows_body => {
+                eprintln!(
+                    "{}",
+                    format!("⚠️  Warning: Ignoring body file '{}' in {} request", path, method)
+                        .yellow()
+                );
+            }
+            RequestItem::RawBodyFile(_) => {}
             RequestItem::FormFile(key, filepath) => {
-                files.push((key.clone(), filepath.clone()));
+                form_parts.push(FormPart::File(key.clone(), filepath.clone()));
                 if let Some(ref mut info) = verbose_info {
                     info.add_file(key.clone(), filepath.clone());
                 }
@@ -225,53 +532,99 @@ pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
         req_builder = req_builder.query(&query_params);
     }
 
-    let body_type = detect_body_type(&args.items);
+    let json_body = match body_type {
+        Some(BodyType::Json) if allows_body => {
+            Some(build_json_body(&args.items, args.duplicate_keys)?)
+        }
+        _ => None,
+    };
+    let body_len = if allows_body {
+        args.items
+            .iter()
+            .filter(|item| matches!(item, RequestItem::Body(_, _) | RequestItem::RawJson(_, _)))
+            .count()
+    } else {
+        0
+    };
 
     if args.verbose {
         eprintln!("{} {:?}", "Detected body type:".yellow(), body_type);
         eprintln!(
             "{} {} files, {} body fields",
             "Request contains:".yellow(),
-            files.len(),
-            body.len()
+            form_parts
+                .iter()
+                .filter(|part| matches!(part, FormPart::File(_, _)))
+                .count(),
+            body_len
         );
     }
 
-    let resp = match body_type {
-        Some(BodyType::Multipart) => {
-            // Multipart form (file upload)
-            use reqwest::multipart;
+    let has_content_type = args.items.iter().any(|item| {
+        matches!(item, RequestItem::Header(key, _) if key.eq_ignore_ascii_case("content-type"))
+    });
 
-            let mut form = multipart::Form::new();
+    let req_builder = match (body_type, json_body) {
+        (Some(BodyType::Raw), _) => {
+            // Raw body from --raw, @file or stdin
+            let (data, content_type) = raw_body.unwrap_or_default();
 
-            // Add text fields
-            for (key, value) in form_fields {
-                form = form.text(key, value);
+            if let Some(content_type) = content_type.as_ref().filter(|_| !has_content_type) {
+                req_builder = req_builder.header(reqwest::header::CONTENT_TYPE, content_type);
+                if let Some(ref mut info) = verbose_info {
+                    info.add_header("Content-Type".to_string(), content_type.clone());
+                }
+            }
+
+            if let Some(ref mut info) = verbose_info {
+                match std::str::from_utf8(&data) {
+                    Ok(text) => info.set_body(text.to_string()),
+                    Err(_) => info.set_body(format!("<{} bytes of binary data>", data.len())),
+                }
             }
 
-            // Add files
-            for (key, filepath) in files {
-                let file_content = tokio::fs::read(&filepath)
-                    .await
-                    .map_err(|e| anyhow!("Failed to read file '{}': {}", filepath, e))?;
+            if let Some(info) = verbose_info {
+                info.print();
+            }
 
-                // Guess MIME type
-                let mime_type = mime_guess::from_path(&filepath)
-                    .first_or_octet_stream()
-                    .to_string();
+            req_builder.body(data)
+        }
 
-                // Extract filename
-                let filename = std::path::Path::new(&filepath)
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("file")
-                    .to_string();
+        (Some(BodyType::Multipart), _) => {
+            // Multipart form (file upload)
+            use reqwest::multipart;
 
-                let part = multipart::Part::bytes(file_content)
-                    .file_name(filename)
-                    .mime_str(&mime_type)?;
+            let mut form = multipart::Form::new();
 
-                form = form.part(key, part);
+            for part in form_parts {
+                match part {
+                    FormPart::Text(key, value) => {
+                        form = form.text(key, value);
+                    }
+                    FormPart::File(key, filepath) => {
+                        let file_content = tokio::fs::read(&filepath)
+                            .await
+                            .map_err(|e| anyhow!("Failed to read file '{}': {}", filepath, e))?;
+
+                        // Guess MIME type
+                        let mime_type = mime_guess::from_path(&filepath)
+                            .first_or_octet_stream()
+                            .to_string();
+
+                        // Extract filename
+                        let filename = std::path::Path::new(&filepath)
+                            .file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("file")
+                            .to_string();
+
+                        let part = multipart::Part::bytes(file_content)
+                            .file_name(filename)
+                            .mime_str(&mime_type)?;
+
+                        form = form.part(key, part);
+                    }
+                }
             }
             if let Some(ref mut info) = verbose_info {
                 info.add_header(
@@ -284,10 +637,35 @@ pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
                 info.print();
             }
 
-            req_builder.multipart(form).send().await?
+            req_builder.multipart(form)
         }
 
-        Some(BodyType::Json) | None if !body.is_empty() => {
+        (Some(BodyType::Form), _) if allows_body => {
+            // application/x-www-form-urlencoded
+            let fields: Vec<(String, String)> = form_parts
+                .into_iter()
+                .filter_map(|part| match part {
+                    FormPart::Text(key, value) => Some((key, value)),
+                    FormPart::File(_, _) => None,
+                })
+                .collect();
+
+            if let Some(ref mut info) = verbose_info {
+                info.set_body(serde_urlencoded::to_string(&fields)?);
+                info.add_header(
+                    "Content-Type".to_string(),
+                    "application/x-www-form-urlencoded".to_string(),
+                );
+            }
+
+            if let Some(info) = verbose_info {
+                info.print();
+            }
+
+            req_builder.form(&fields)
+        }
+
+        (_, Some(body)) => {
             // application/json
             let json_body = serde_json::to_string(&body)?;
 
@@ -300,7 +678,7 @@ pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
                 info.print();
             }
 
-            req_builder.json(&body).send().await?
+            req_builder.json(&body)
         }
 
         _ => {
@@ -309,7 +687,45 @@ pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
                 info.print();
             }
 
-            req_builder.send().await?
+            req_builder
+        }
+    };
+
+    let resp = if let Some((config, token_client)) = &oauth {
+        oauth::send(client, req_builder.build()?, config, token_client).await?
+    } else {
+        match &auth {
+            Some(Auth::Digest { username, password }) => {
+                let password = password
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("The Digest password for {} is missing: pass --auth USER:PASSWORD", username))?;
+                digest::send(client, req_builder.build()?, username, password).await?
+            }
+            Some(Auth::AwsSigV4 { access_key, secret_key }) => {
+                let mut request = req_builder.build()?;
+                let credentials = aws::Credentials::resolve(
+                    access_key.as_deref(),
+                    secret_key.as_deref(),
+                    args.aws_profile.as_deref(),
+                )?;
+                let (region, service) = aws::region_and_service(args, request.url())?;
+                aws::sign(&mut request, &credentials, &region, &service, time::OffsetDateTime::now_utc())?;
+                client.execute(request).await?
+            }
+            Some(Auth::Hmac { key_id, secret }) => {
+                let mut request = req_builder.build()?;
+                // The client only adds its default headers when sending, so
+                // add them now for templates like {header:user-agent}
+                for (name, value) in default_headers(args)?.iter() {
+                    if !request.headers().contains_key(name) {
+                        request.headers_mut().insert(name.clone(), value.clone());
+                    }
+                }
+                let signer = hmac_auth::Signer::from_args(args)?;
+                hmac_auth::sign(&mut request, key_id.as_deref(), secret, &signer, time::OffsetDateTime::now_utc())?;
+                client.execute(request).await?
+            }
+            _ => req_builder.send().await?,
         }
     };
 
@@ -317,6 +733,14 @@ pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
         t.record_first_byte();
     }
 
+    if let Some(handshake) = &handshake {
+        if args.print_cert {
+            print_certificates(handshake);
+        } else {
+            println!("{} {}", "TLS:".cyan().bold(), handshake.summary());
+        }
+    }
+
     // handle download pattern
     if args.download || args.output.is_some() {
         let filename = determine_filename(args, &resp);
@@ -346,6 +770,7 @@ pub async fn execute_request(cli: &Cli, client: &Client) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clap::Parser;
 
     #[test]
     fn test_detect_body_type_json() {
@@ -353,7 +778,7 @@ mod tests {
             RequestItem::Body("name".to_string(), "alice".to_string()),
             RequestItem::Body("age".to_string(), "30".to_string()),
         ];
-        assert_eq!(detect_body_type(&items), Some(BodyType::Json));
+        assert_eq!(detect_body_type(&items, false), Some(BodyType::Json));
     }
 
     #[test]
@@ -362,7 +787,7 @@ mod tests {
             RequestItem::Body("title".to_string(), "test".to_string()),
             RequestItem::FormFile("file".to_string(), "/path/to/file".to_string()),
         ];
-        assert_eq!(detect_body_type(&items), Some(BodyType::Multipart));
+        assert_eq!(detect_body_type(&items, false), Some(BodyType::Multipart));
     }
 
     #[test]
@@ -371,7 +796,170 @@ mod tests {
             RequestItem::Header("Authorization".to_string(), "Bearer token".to_string()),
             RequestItem::QueryParam("page".to_string(), "1".to_string()),
         ];
-        assert_eq!(detect_body_type(&items), None);
+        assert_eq!(detect_body_type(&items, false), None);
+    }
+
+    #[test]
+    fn test_detect_body_type_form() {
+        let items = vec![
+            RequestItem::Body("username".to_string(), "alice".to_string()),
+            RequestItem::Body("password".to_string(), "secret".to_string()),
+        ];
+        assert_eq!(detect_body_type(&items, true), Some(BodyType::Form));
+    }
+
+    #[test]
+    fn test_detect_body_type_form_with_file() {
+        // Files switch --form to multipart
+        let items = vec![
+            RequestItem::Body("title".to_string(), "test".to_string()),
+            RequestItem::FormFile("file".to_string(), "/path/to/file".to_string()),
+        ];
+        assert_eq!(detect_body_type(&items, true), Some(BodyType::Multipart));
+    }
+
+    #[test]
+    fn test_detect_body_type_form_without_body() {
+        let items = vec![RequestItem::QueryParam("page".to_string(), "1".to_string())];
+        assert_eq!(detect_body_type(&items, true), None);
+    }
+
+    #[test]
+    fn test_detect_body_type_raw_json() {
+        let items = vec![RequestItem::RawJson("age".to_string(), serde_json::json!(30))];
+        assert_eq!(detect_body_type(&items, false), Some(BodyType::Json));
+    }
+
+    #[test]
+    fn test_build_json_body_typed_values() {
+        let items = vec![
+            RequestItem::Body("name".to_string(), "alice".to_string()),
+            RequestItem::RawJson("age".to_string(), serde_json::json!(30)),
+            RequestItem::RawJson("active".to_string(), serde_json::json!(true)),
+            RequestItem::RawJson("tags".to_string(), serde_json::json!(["a", "b"])),
+            RequestItem::Header("Accept".to_string(), "application/json".to_string()),
+        ];
+        assert_eq!(
+            build_json_body(&items, DuplicateKeys::Array).unwrap(),
+            serde_json::json!({
+                "name": "alice",
+                "age": 30,
+                "active": true,
+                "tags": ["a", "b"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_json_body_empty() {
+        let items = vec![RequestItem::QueryParam("page".to_string(), "1".to_string())];
+        assert_eq!(build_json_body(&items, DuplicateKeys::Array).unwrap(), serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_build_json_body_nested() {
+        let items = vec![
+            RequestItem::Body("user[name]".to_string(), "alice".to_string()),
+            RequestItem::Body("user[roles][]".to_string(), "admin".to_string()),
+            RequestItem::RawJson("items[0][id]".to_string(), serde_json::json!(7)),
+        ];
+        assert_eq!(
+            build_json_body(&items, DuplicateKeys::Array).unwrap(),
+            serde_json::json!({
+                "user": {"name": "alice", "roles": ["admin"]},
+                "items": [{"id": 7}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_json_body_duplicates() {
+        let items = vec![
+            RequestItem::Body("tag".to_string(), "a".to_string()),
+            RequestItem::Body("name".to_string(), "alice".to_string()),
+            RequestItem::Body("tag".to_string(), "b".to_string()),
+        ];
+        let body = build_json_body(&items, DuplicateKeys::Array).unwrap();
+        assert_eq!(
+            serde_json::to_string(&body).unwrap(),
+            r#"{"tag":["a","b"],"name":"alice"}"#
+        );
+
+        assert!(build_json_body(&items, DuplicateKeys::Error).is_err());
+    }
+
+    #[test]
+    fn test_build_json_body_conflict() {
+        let items = vec![
+            RequestItem::Body("user[name]".to_string(), "alice".to_string()),
+            RequestItem::Body("user[]".to_string(), "bob".to_string()),
+        ];
+        assert!(build_json_body(&items, DuplicateKeys::Array).is_err());
+    }
+
+    fn raw_args(raw: Option<&str>, items: Vec<RequestItem>) -> RequestArgs {
+        let mut args = RequestArgs::try_parse_from(["httprs", "http://example.com"]).unwrap();
+        args.raw = raw.map(String::from);
+        args.items = items;
+        args
+    }
+
+    #[test]
+    fn test_raw_body_source_data() {
+        let args = raw_args(Some("<xml/>"), vec![]);
+        assert_eq!(
+            raw_body_source(&args, false).unwrap(),
+            Some(RawSource::Data("<xml/>".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_raw_body_source_file() {
+        let args = raw_args(None, vec![RequestItem::RawBodyFile("payload.json".to_string())]);
+        assert_eq!(
+            raw_body_source(&args, true).unwrap(),
+            Some(RawSource::File("payload.json".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_raw_body_source_stdin() {
+        let args = raw_args(None, vec![]);
+        assert_eq!(raw_body_source(&args, true).unwrap(), Some(RawSource::Stdin));
+        assert_eq!(raw_body_source(&args, false).unwrap(), None);
+
+        let mut ignored = raw_args(None, vec![]);
+        ignored.ignore_stdin = true;
+        assert_eq!(raw_body_source(&ignored, true).unwrap(), None);
+
+        // Body fields take precedence over piped stdin
+        let fields = raw_args(None, vec![RequestItem::Body("a".to_string(), "1".to_string())]);
+        assert_eq!(raw_body_source(&fields, true).unwrap(), None);
+    }
+
+    #[test]
+    fn test_raw_body_source_conflicts() {
+        let both = raw_args(Some("{}"), vec![RequestItem::RawBodyFile("a.json".to_string())]);
+        assert!(raw_body_source(&both, false).is_err());
+
+        let with_fields = raw_args(Some("{}"), vec![RequestItem::Body("a".to_string(), "1".to_string())]);
+        assert!(raw_body_source(&with_fields, false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_raw_body_content_type() {
+        let (data, content_type) = load_raw_body(&RawSource::Data(r#"{"a":1}"#.to_string()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data, br#"{"a":1}"#);
+        assert_eq!(content_type.as_deref(), Some("application/json"));
+
+        let (_, content_type) = load_raw_body(&RawSource::Data("<xml/>".to_string()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(content_type, None);
     }
 
     #[test]
@@ -433,7 +1021,7 @@ mod tests {
             RequestItem::FormFile("file".to_string(), "/path/to/file".to_string()),
             RequestItem::Body("description".to_string(), "desc".to_string()),
         ];
-        assert_eq!(detect_body_type(&items), Some(BodyType::Multipart));
+        assert_eq!(detect_body_type(&items, false), Some(BodyType::Multipart));
     }
 
     #[test]
@@ -442,7 +1030,7 @@ mod tests {
             RequestItem::Header("Authorization".to_string(), "Bearer token".to_string()),
             RequestItem::Header("Accept".to_string(), "application/json".to_string()),
         ];
-        assert_eq!(detect_body_type(&items), None);
+        assert_eq!(detect_body_type(&items, false), None);
     }
 
     #[test]
@@ -451,7 +1039,32 @@ mod tests {
             RequestItem::QueryParam("page".to_string(), "1".to_string()),
             RequestItem::QueryParam("limit".to_string(), "10".to_string()),
         ];
-        assert_eq!(detect_body_type(&items), None);
+        assert_eq!(detect_body_type(&items, false), None);
+    }
+
+    #[test]
+    fn test_netrc_auth_only_for_username_password_schemes() {
+        let parse = |argv: &[&str]| RequestArgs::try_parse_from(argv).unwrap();
+        let entry = |_: &str| {
+            Some(Auth::Basic {
+                username: "alice".to_string(),
+                password: Some("s3cret".to_string()),
+            })
+        };
+
+        let args = parse(&["httprs", "https://api.example.com"]);
+        assert_eq!(netrc_auth(&args, Some("api.example.com"), entry), entry(""));
+        let args = parse(&["httprs", "https://api.example.com", "--auth-type", "digest"]);
+        assert_eq!(netrc_auth(&args, Some("api.example.com"), entry), entry(""));
+
+        // A login and password aren't an HMAC key id and secret
+        let args = parse(&["httprs", "https://api.example.com", "--auth-type", "hmac"]);
+        assert_eq!(netrc_auth(&args, Some("api.example.com"), entry), None);
+        let args = parse(&["httprs", "https://api.example.com", "--auth-type", "bearer"]);
+        assert_eq!(netrc_auth(&args, Some("api.example.com"), entry), None);
+
+        let args = parse(&["httprs", "https://api.example.com", "--ignore-netrc"]);
+        assert_eq!(netrc_auth(&args, Some("api.example.com"), entry), None);
     }
 
     #[test]
@@ -459,5 +1072,6 @@ mod tests {
         assert_eq!(BodyType::Json, BodyType::Json);
         assert_eq!(BodyType::Multipart, BodyType::Multipart);
         assert_ne!(BodyType::Json, BodyType::Multipart);
+        assert_ne!(BodyType::Form, BodyType::Multipart);
     }
 }
@@ -3,6 +3,8 @@ use colored::Colorize;
 use mime::Mime;
 use reqwest::{header, Response};
 use syntect::parsing::SyntaxReference;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 use syntect::{
     easy::HighlightLines,
     highlighting::{Style, ThemeSet},
@@ -11,16 +13,79 @@ use syntect::{
 };
 
 use crate::cli::OutputFilter;
+use crate::cookies::describe_set_cookie;
+use crate::tls::{CertificateInfo, Handshake};
 
 pub fn print_status(resp: &Response) {
     let status = format!("{:?} {}", resp.version(), resp.status()).blue();
     println!("{}\n", status);
 }
 
+/// `--print-cert`: the server's certificate chain, leaf first, and what the
+/// handshake negotiated.
+pub fn print_certificates(handshake: &Handshake) {
+    let now = OffsetDateTime::now_utc();
+    println!("{}", "Certificate chain:".cyan().bold());
+    for (depth, der) in handshake.chain.iter().enumerate() {
+        let cert = match CertificateInfo::parse(der) {
+            Ok(cert) => cert,
+            Err(e) => {
+                println!("  [{}] {}", depth, e.to_string().red());
+                continue;
+            }
+        };
+        println!("  [{}] {}", depth, cert.subject.bold());
+        println!("      {} {}", "Issuer: ".green(), cert.issuer);
+        if !cert.sans.is_empty() {
+            println!("      {} {}", "SANs:   ".green(), cert.sans.join(", "));
+        }
+
+        let days = cert.days_remaining(now);
+        let remaining = if cert.not_before > now {
+            "not valid yet".red()
+        } else if days < 0 {
+            format!("expired {} days ago", -days).red()
+        } else if days < 30 {
+            format!("{} days left", days).yellow()
+        } else {
+            format!("{} days left", days).normal()
+        };
+        println!(
+            "      {} {} to {} ({})",
+            "Valid:  ".green(),
+            cert.not_before.format(&Rfc3339).unwrap_or_default(),
+            cert.not_after.format(&Rfc3339).unwrap_or_default(),
+            remaining
+        );
+        println!("      {} {}", "Key:    ".green(), cert.key_type);
+        println!("      {} {}", "SHA-256:".green(), cert.fingerprint);
+        println!("      {} {}", "Pin:    ".green(), cert.pin);
+    }
+    if let Some(error) = &handshake.verify_error {
+        println!("{} {}", "Verification failed:".red().bold(), error);
+    }
+    println!("{} {}\n", "TLS:".cyan().bold(), handshake.summary());
+}
+
 pub fn print_headers(resp: &Response) {
     for (name, value) in resp.headers() {
         println!("{}: {:?}", name.to_string().green(), value);
     }
+
+    let cookies: Vec<String> = resp
+        .headers()
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(describe_set_cookie)
+        .collect();
+    if !cookies.is_empty() {
+        println!();
+        println!("{}", "Cookies:".cyan().bold());
+        for cookie in cookies {
+            println!("  🍪 {}", cookie);
+        }
+    }
     println!();
 }
 
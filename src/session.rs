@@ -0,0 +1,346 @@
+use anyhow::{anyhow, Result};
+use cookie_store::Cookie;
+use reqwest::Url;
+use reqwest_cookie_store::{CookieStore, CookieStoreMutex};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::auth::Auth;
+use crate::cli::{RequestArgs, RequestItem};
+use crate::paths::{config_dir, write_private};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// A named session persisted as JSON: cookies, custom headers and
+/// Basic, Digest or Bearer auth.
+///
+/// Stored under `<config dir>/sessions/<host>/<name>.json`, or at the given
+/// path when the session name contains a `/` or ends with `.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub headers: Vec<SessionHeader>,
+    #[serde(default)]
+    pub auth: Option<Auth>,
+    #[serde(default)]
+    pub cookies: Vec<Cookie<'static>>,
+
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(skip)]
+    read_only: bool,
+}
+
+/// Resolves a session name to its file for the host of `url`.
+pub fn session_path(name: &str, url: &str) -> Result<PathBuf> {
+    if name.contains('/') || name.contains(std::path::MAIN_SEPARATOR) || name.ends_with(".json") {
+        return Ok(PathBuf::from(name));
+    }
+    if name.is_empty() || name.starts_with('.') {
+        return Err(anyhow!("Invalid session name: '{}'", name));
+    }
+
+    let url = Url::parse(url)?;
+    let host = url.host_str().unwrap_or("localhost");
+    let host = match url.port() {
+        Some(port) => format!("{}_{}", host, port),
+        None => host.to_string(),
+    };
+    Ok(config_dir()?
+        .join("sessions")
+        .join(host)
+        .join(format!("{}.json", name)))
+}
+
+/// Headers describing the body or conditional requests are not persisted.
+fn is_session_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    !name.starts_with("content-") && !name.starts_with("if-")
+}
+
+/// Basic, Digest and Bearer credentials are persisted; the OAuth2 client
+/// secret, the HMAC secret and AWS keys are signing secrets and are not
+/// written to the session file.
+fn is_session_auth(auth: &Auth) -> bool {
+    matches!(auth, Auth::Basic { .. } | Auth::Digest { .. } | Auth::Bearer(_))
+}
+
+impl Session {
+    /// Opens the session selected by `--session` / `--session-read-only`.
+    pub fn from_args(args: &RequestArgs) -> Result<Option<Self>> {
+        match (&args.session, &args.session_read_only) {
+            (Some(name), _) => Ok(Some(Self::load(&session_path(name, &args.url)?, false)?)),
+            (None, Some(name)) => Ok(Some(Self::load(&session_path(name, &args.url)?, true)?)),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Loads a session file, or starts an empty session if it doesn't exist yet.
+    pub fn load(path: &Path, read_only: bool) -> Result<Self> {
+        let mut session = if path.exists() {
+            let data = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read session '{}': {}", path.display(), e))?;
+            serde_json::from_str(&data)
+                .map_err(|e| anyhow!("Invalid session file '{}': {}", path.display(), e))?
+        } else {
+            Session::default()
+        };
+        session.path = path.to_path_buf();
+        session.read_only = read_only;
+        Ok(session)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A cookie store seeded with the session's cookies, to share with the client.
+    pub fn cookie_jar(&self) -> Arc<CookieStoreMutex> {
+        let store = CookieStore::from_cookies(
+            self.cookies.iter().cloned().map(Ok::<_, std::convert::Infallible>),
+            false,
+        )
+        .unwrap_or_default();
+        Arc::new(CookieStoreMutex::new(store))
+    }
+
+    /// Adds the session's headers and auth to the request. Headers and auth
+    /// given on the command line take precedence.
+    pub fn apply(&self, args: &mut RequestArgs) {
+        let mut items: Vec<RequestItem> = self
+            .headers
+            .iter()
+            .filter(|header| {
+                !args.items.iter().any(|item| match item {
+                    RequestItem::Header(name, _) | RequestItem::EmptyHeader(name) => {
+                        name.eq_ignore_ascii_case(&header.name)
+                    }
+                    _ => false,
+                })
+            })
+            .map(|header| RequestItem::Header(header.name.clone(), header.value.clone()))
+            .collect();
+        items.append(&mut args.items);
+        args.items = items;
+
+        if args.auth.is_none() {
+            args.auth = self.auth.clone();
+        }
+    }
+
+    /// Records the request's headers and auth (unless it's a signing secret),
+    /// and the cookies in `jar`.
+    pub fn update(&mut self, args: &RequestArgs, jar: &CookieStoreMutex) {
+        for item in &args.items {
+            let RequestItem::Header(name, value) = item else {
+                continue;
+            };
+            if !is_session_header(name) {
+                continue;
+            }
+            self.headers.retain(|header| !header.name.eq_ignore_ascii_case(name));
+            // `Header:` removes the header from the session
+            if !value.is_empty() {
+                self.headers.push(SessionHeader {
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        if let Some(auth) = args.auth.as_ref().filter(|auth| is_session_auth(auth)) {
+            self.auth = Some(auth.clone());
+        }
+
+        if let Ok(store) = jar.lock() {
+            self.cookies = store.iter_unexpired().cloned().collect();
+        }
+    }
+
+    /// Writes the session back to disk, unless it was opened read-only.
+    pub fn save(&self) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let data = serde_json::to_string_pretty(self)?;
+        write_private(&self.path, data.as_bytes())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn temp_session(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("httprs-{}-session-{}.json", std::process::id(), name))
+    }
+
+    fn parse_args(argv: &[&str]) -> RequestArgs {
+        RequestArgs::try_parse_from(argv).unwrap()
+    }
+
+    #[test]
+    fn session_path_by_name_uses_host() {
+        let path = session_path("dev", "https://api.example.com/users").unwrap();
+        assert!(path.ends_with("sessions/api.example.com/dev.json"));
+
+        let path = session_path("dev", "http://localhost:3000/").unwrap();
+        assert!(path.ends_with("sessions/localhost_3000/dev.json"));
+    }
+
+    #[test]
+    fn session_path_explicit_file() {
+        assert_eq!(
+            session_path("./my-session.json", "https://example.com").unwrap(),
+            PathBuf::from("./my-session.json")
+        );
+        assert_eq!(
+            session_path("/tmp/s", "https://example.com").unwrap(),
+            PathBuf::from("/tmp/s")
+        );
+        assert!(session_path("", "https://example.com").is_err());
+        assert!(session_path("..", "https://example.com").is_err());
+    }
+
+    #[test]
+    fn session_update_and_apply() {
+        let mut session = Session::default();
+        let args = parse_args(&[
+            "httprs",
+            "https://example.com",
+            "X-Api-Version:2",
+            "Content-Type:application/json",
+            "If-None-Match:abc",
+            "-a",
+            "alice:secret",
+        ]);
+        session.update(&args, &CookieStoreMutex::default());
+
+        assert_eq!(
+            session.headers,
+            vec![SessionHeader {
+                name: "X-Api-Version".into(),
+                value: "2".into()
+            }]
+        );
+        assert_eq!(
+            session.auth,
+            Some(Auth::Basic {
+                username: "alice".into(),
+                password: Some("secret".into())
+            })
+        );
+
+        // Stored headers and auth are added to a later request...
+        let mut next = parse_args(&["httprs", "https://example.com", "page==2"]);
+        session.apply(&mut next);
+        assert_eq!(
+            next.items,
+            vec![
+                RequestItem::Header("X-Api-Version".into(), "2".into()),
+                RequestItem::QueryParam("page".into(), "2".into()),
+            ]
+        );
+        assert_eq!(next.auth, session.auth);
+
+        // ...but the command line wins
+        let mut next = parse_args(&["httprs", "https://example.com", "x-api-version:3", "-a", "bob"]);
+        session.apply(&mut next);
+        assert_eq!(
+            next.items,
+            vec![RequestItem::Header("x-api-version".into(), "3".into())]
+        );
+        assert_eq!(
+            next.auth,
+            Some(Auth::Basic {
+                username: "bob".into(),
+                password: None
+            })
+        );
+    }
+
+    #[test]
+    fn session_skips_signing_secrets() {
+        let mut session = Session::default();
+        let mut args = parse_args(&["httprs", "https://example.com"]);
+        args.auth = Some(Auth::Hmac {
+            key_id: Some("key-1".into()),
+            secret: "s3cret".into(),
+        });
+        session.update(&args, &CookieStoreMutex::default());
+        args.auth = Some(Auth::OAuth2 {
+            client_id: "my-client".into(),
+            client_secret: Some("s3cret".into()),
+        });
+        session.update(&args, &CookieStoreMutex::default());
+        assert_eq!(session.auth, None);
+    }
+
+    #[test]
+    fn session_header_removed_by_empty_value() {
+        let mut session = Session::default();
+        session.headers.push(SessionHeader {
+            name: "X-Debug".into(),
+            value: "1".into(),
+        });
+        let args = parse_args(&["httprs", "https://example.com", "X-Debug:"]);
+        session.update(&args, &CookieStoreMutex::default());
+        assert!(session.headers.is_empty());
+    }
+
+    #[test]
+    fn session_round_trip_with_cookies() {
+        let path = temp_session("round-trip");
+        let _ = std::fs::remove_file(&path);
+
+        let mut session = Session::load(&path, false).unwrap();
+        assert!(session.cookies.is_empty());
+
+        let jar = session.cookie_jar();
+        let url = Url::parse("https://example.com/login").unwrap();
+        jar.lock()
+            .unwrap()
+            .parse("sid=abc123; Path=/; Max-Age=3600", &url)
+            .unwrap();
+
+        let args = parse_args(&["httprs", "https://example.com/login", "X-Team:core"]);
+        session.update(&args, &jar);
+        session.save().unwrap();
+
+        let loaded = Session::load(&path, false).unwrap();
+        assert_eq!(loaded.headers, session.headers);
+        assert_eq!(loaded.cookies.len(), 1);
+        assert_eq!(loaded.cookies[0].name(), "sid");
+
+        let jar = loaded.cookie_jar();
+        let values: Vec<(String, String)> = jar
+            .lock()
+            .unwrap()
+            .get_request_values(&Url::parse("https://example.com/api").unwrap())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(values, vec![("sid".to_string(), "abc123".to_string())]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn session_read_only_is_not_saved() {
+        let path = temp_session("read-only");
+        let _ = std::fs::remove_file(&path);
+
+        let session = Session::load(&path, true).unwrap();
+        session.save().unwrap();
+        assert!(!path.exists());
+    }
+}
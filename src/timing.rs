@@ -3,8 +3,12 @@ use colored::Colorize;
 #[derive(Debug,Clone)]
 pub struct RequestTimer{
     start:Instant,
+    // Connection phases are not exposed by reqwest yet; kept for when they are.
+    #[allow(dead_code)]
     dns_lookup:Option<Duration>,
+    #[allow(dead_code)]
     tcp_connect:Option<Duration>,
+    #[allow(dead_code)]
     tls_handshake:Option<Duration>,
     first_byte:Option<Duration>,
     total:Option<Duration>,
@@ -131,6 +135,6 @@ mod tests {
     fn test_format_duration() {
         assert_eq!(format_duration(Duration::from_micros(500)), "500.00 µs");
         assert_eq!(format_duration(Duration::from_millis(50)), "50.00 ms");
-        assert_eq!(format_duration(Duration::from_secs(2)), "2000.00 ms");
+        assert_eq!(format_duration(Duration::from_secs(2)), "2.00 s");
     }
 }
\ No newline at end of file